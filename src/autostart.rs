@@ -9,7 +9,9 @@ use winapi::shared::minwindef::HKEY;
 use winapi::um::winnt::{KEY_SET_VALUE, REG_OPTION_NON_VOLATILE, REG_SZ};
 use winapi::um::winreg::{RegCreateKeyExW, RegDeleteKeyValueW, RegSetValueExW, HKEY_CURRENT_USER};
 
-use crate::{str_to_wide, Result};
+use grout::Result;
+
+use crate::str_to_wide;
 
 pub unsafe fn toggle_autostart_registry_key(enabled: bool) -> Result<()> {
     let mut app_path =
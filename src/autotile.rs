@@ -0,0 +1,144 @@
+use std::mem;
+
+use grout::config::Config;
+use grout::tiling::{layout_from_name, sync_order, MasterStack, LAYOUTS};
+
+use crate::monitor::{get_monitors, Monitor};
+use crate::window::{get_visible_windows, Window};
+use crate::ACTIVE_PROFILE;
+
+const MASTER_RATIO_STEP: f32 = 0.05;
+
+/// Runtime state of the auto-tiling mode
+pub struct AutoTile {
+    pub enabled: bool,
+    layout: usize,
+    master_ratio: f32,
+    order: Vec<Window>,
+    /// Windows each monitor was last tiled with, in order
+    tiled: Vec<(Monitor, Vec<Window>)>,
+}
+
+impl From<&Config> for AutoTile {
+    fn from(config: &Config) -> Self {
+        let layout = LAYOUTS
+            .iter()
+            .position(|name| *name == config.auto_tile_layout)
+            .unwrap_or_default();
+
+        AutoTile {
+            enabled: config.auto_tile,
+            layout,
+            master_ratio: config.master_ratio,
+            order: vec![],
+            tiled: vec![],
+        }
+    }
+}
+
+impl AutoTile {
+    pub fn toggle(&mut self, config: &Config) {
+        self.enabled = !self.enabled;
+        self.order.clear();
+        self.tiled.clear();
+
+        if self.enabled {
+            self.apply(config);
+        }
+    }
+
+    pub fn cycle_layout(&mut self, config: &Config) {
+        self.layout = (self.layout + 1) % LAYOUTS.len();
+        self.apply(config);
+    }
+
+    pub fn adjust_master_ratio(&mut self, grow: bool, config: &Config) {
+        let step = if grow {
+            MASTER_RATIO_STEP
+        } else {
+            -MASTER_RATIO_STEP
+        };
+
+        self.master_ratio = (self.master_ratio + step).clamp(0.1, 0.9);
+        self.apply(config);
+    }
+
+    /// Retiles the monitors whose windows were opened, closed or moved to another
+    /// monitor since the last time. Other monitors are left alone, so their windows
+    /// aren't restored & repositioned on every window shown or hidden.
+    pub fn refresh(&mut self, config: &Config) {
+        if !self.enabled {
+            return;
+        }
+
+        sync_order(&mut self.order, &tileable_windows());
+
+        self.tile(config, false);
+    }
+
+    /// Retiles every monitor, such as after the layout changed
+    pub fn apply(&mut self, config: &Config) {
+        if !self.enabled {
+            return;
+        }
+
+        sync_order(&mut self.order, &tileable_windows());
+
+        self.tile(config, true);
+    }
+
+    fn tile(&mut self, config: &Config, all_monitors: bool) {
+        let layout =
+            layout_from_name(LAYOUTS[self.layout], self.master_ratio).unwrap_or_else(|| {
+                Box::new(MasterStack {
+                    ratio: self.master_ratio,
+                })
+            });
+
+        let previous = mem::take(&mut self.tiled);
+
+        for monitor in get_monitors() {
+            let windows = self
+                .order
+                .iter()
+                .copied()
+                .filter(|window| window.monitor() == monitor)
+                .collect::<Vec<_>>();
+
+            let unchanged = previous
+                .iter()
+                .any(|(tiled_monitor, tiled)| *tiled_monitor == monitor && *tiled == windows);
+
+            if all_monitors || !unchanged {
+                let monitor_dpi = monitor.dpi();
+                let work_area = monitor.work_area();
+                let spacing =
+                    config.spacing(&ACTIVE_PROFILE.lock().unwrap(), &monitor.descriptor());
+
+                let area = spacing.padded_area(work_area, monitor_dpi);
+                let margin = spacing.margin(work_area, monitor_dpi);
+
+                let rects = layout.arrange(area, windows.len(), margin);
+
+                for (mut window, mut rect) in windows.iter().copied().zip(rects) {
+                    if window.is_maximized() {
+                        window.restore();
+                    }
+
+                    rect.adjust_for_border(window.transparent_border());
+
+                    window.set_pos(rect, None);
+                }
+            }
+
+            self.tiled.push((monitor, windows));
+        }
+    }
+}
+
+fn tileable_windows() -> Vec<Window> {
+    get_visible_windows()
+        .into_iter()
+        .filter(|window| !window.is_minimized())
+        .collect()
+}
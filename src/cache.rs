@@ -0,0 +1,440 @@
+//! Grid cache remembering the rows & columns used on each monitor & profile.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::format_err;
+use crossbeam_channel::{unbounded, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+
+use crate::grid_config::{migrate_keys, strip_nul_padding, GridConfig, GridConfigKey, GridConfigs};
+use crate::monitor_id::MonitorDescriptor;
+use crate::Result;
+
+/// Version of the grid cache format written by this build
+pub const CACHE_VERSION: u32 = 2;
+
+const CACHE_FILE: &str = "grid.ron";
+
+/// How long to wait for further changes before writing the cache, so holding down
+/// a key that adds rows doesn't write the file for every row
+pub const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest `flush_cache` waits for a pending write on exit
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upgrades the grids from each version to the next, starting at version 1
+const MIGRATIONS: [fn(&mut GridConfigs); (CACHE_VERSION - 1) as usize] = [
+    // Version 1 keyed grids on the whole device name buffer, NUL padding included
+    strip_nul_padding,
+];
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    grids: GridConfigs,
+}
+
+enum CacheMessage {
+    Save(GridConfigs),
+    Flush(Sender<()>),
+}
+
+/// Directory the grid cache is kept in
+pub fn cache_dir() -> Option<PathBuf> {
+    let mut dir = dirs::config_dir()?;
+    dir.push("grout");
+    dir.push("cache");
+
+    Some(dir)
+}
+
+/// Grids remembered for each monitor & profile. Changes are kept in memory and
+/// written to disk in the background, so reads see them before they're written.
+pub struct GridCache {
+    grids: Mutex<GridConfigs>,
+    writer: Sender<CacheMessage>,
+}
+
+impl GridCache {
+    /// Reads the cache in `dir` once. Changes are written back to it once none were
+    /// made for `delay`. Without a directory, grids are only kept in memory.
+    pub fn open(dir: Option<PathBuf>, delay: Duration) -> GridCache {
+        let grids = dir.as_deref().map(read_cache).unwrap_or_default();
+
+        GridCache {
+            grids: Mutex::new(grids),
+            writer: spawn_cache_writer(dir, delay),
+        }
+    }
+
+    /// Grid remembered for the profile & monitor of `key`
+    pub fn get(&self, key: &GridConfigKey) -> Option<GridConfig> {
+        self.grids.lock().unwrap().get(key).cloned()
+    }
+
+    /// Remembers the grid for `key`, writing it to disk after `SAVE_DELAY`
+    pub fn insert(&self, key: GridConfigKey, grid: GridConfig) {
+        let mut grids = self.grids.lock().unwrap();
+
+        grids.insert(key, grid);
+        self.save(&grids);
+    }
+
+    /// Rekeys grids saved under the device names of `monitors` to their stable ids
+    pub fn migrate_keys(&self, monitors: &[MonitorDescriptor]) {
+        let mut grids = self.grids.lock().unwrap();
+
+        if migrate_keys(&mut grids, monitors) {
+            self.save(&grids);
+        }
+    }
+
+    /// Writes any pending change, waiting for it to be written
+    pub fn flush(&self) {
+        flush_cache(&self.writer);
+    }
+
+    fn save(&self, grids: &GridConfigs) {
+        let _ = self.writer.send(CacheMessage::Save(grids.clone()));
+    }
+}
+
+/// Reads the grid cache in `dir`, migrating it from older versions. A file that
+/// can't be read or parsed is moved aside to a `.bak` file and an empty cache
+/// returned, so it isn't overwritten by the next save.
+pub fn read_cache(dir: &Path) -> GridConfigs {
+    let path = dir.join(CACHE_FILE);
+
+    let grids = match fs::read_to_string(&path) {
+        Ok(contents) => parse_cache(&contents),
+        Err(e) if e.kind() == ErrorKind::NotFound => return GridConfigs::default(),
+        Err(e) => Err(e.into()),
+    };
+
+    grids.unwrap_or_else(|_| {
+        let _ = back_up(&path);
+
+        GridConfigs::default()
+    })
+}
+
+/// Writes the cache to a temporary file first and renames it over the old one, so
+/// an interrupted write can't leave a half written cache behind
+pub fn write_cache(dir: &Path, grids: &GridConfigs) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let cache = CacheFile {
+        version: CACHE_VERSION,
+        grids: grids.clone(),
+    };
+
+    let temp_path = dir.join(format!("{}.tmp", CACHE_FILE));
+
+    fs::write(&temp_path, ron::ser::to_string(&cache)?)?;
+    fs::rename(&temp_path, dir.join(CACHE_FILE))?;
+
+    Ok(())
+}
+
+/// Parses the contents of a grid cache file of any version up to `CACHE_VERSION`,
+/// migrating it to the current one
+pub fn parse_cache(contents: &str) -> Result<GridConfigs> {
+    let mut cache = match ron::de::from_str::<CacheFile>(contents) {
+        Ok(cache) => cache,
+        Err(e) => CacheFile {
+            // Version 1 is the bare map of grids, without a version. Its device names
+            // are padded with NULs, which ron writes as `\0` but can't read back.
+            version: 1,
+            grids: ron::de::from_str(&contents.replace(r"\0", r"\x00")).map_err(|_| e)?,
+        },
+    };
+
+    if cache.version > CACHE_VERSION {
+        return Err(format_err!(
+            "Grid cache version {} is newer than the supported version {}",
+            cache.version,
+            CACHE_VERSION
+        ));
+    }
+
+    if cache.version == 0 {
+        return Err(format_err!("Invalid grid cache version 0"));
+    }
+
+    for migration in MIGRATIONS[(cache.version - 1) as usize..].iter() {
+        migration(&mut cache.grids);
+    }
+
+    Ok(cache.grids)
+}
+
+/// Moves the file to `<name>.<unix time>.bak` next to it
+fn back_up(path: &Path) -> Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format_err!("Invalid cache path"))?;
+
+    let backup = path.with_file_name(format!("{}.{}.bak", file_name, timestamp));
+    fs::rename(path, &backup)?;
+
+    Ok(backup)
+}
+
+/// Writes saved grids to `dir` once no new ones arrived for `delay`
+fn spawn_cache_writer(dir: Option<PathBuf>, delay: Duration) -> Sender<CacheMessage> {
+    let (sender, receiver) = unbounded();
+
+    thread::spawn(move || {
+        let mut pending: Option<GridConfigs> = None;
+
+        loop {
+            let message = if pending.is_some() {
+                receiver.recv_timeout(delay)
+            } else {
+                receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+
+            match message {
+                Ok(CacheMessage::Save(grids)) => pending = Some(grids),
+                Ok(CacheMessage::Flush(done)) => {
+                    write_pending(&dir, &mut pending);
+                    let _ = done.send(());
+                }
+                Err(RecvTimeoutError::Timeout) => write_pending(&dir, &mut pending),
+                Err(RecvTimeoutError::Disconnected) => {
+                    write_pending(&dir, &mut pending);
+                    break;
+                }
+            }
+        }
+    });
+
+    sender
+}
+
+fn write_pending(dir: &Option<PathBuf>, pending: &mut Option<GridConfigs>) {
+    if let (Some(dir), Some(grids)) = (dir, pending.take()) {
+        let _ = write_cache(dir, &grids);
+    }
+}
+
+/// Writes any pending save before the program exits
+fn flush_cache(writer: &Sender<CacheMessage>) {
+    let (done, wait) = unbounded();
+
+    if writer.send(CacheMessage::Flush(done)).is_ok() {
+        let _ = wait.recv_timeout(FLUSH_TIMEOUT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    fn monitor(device_name: &str) -> MonitorDescriptor {
+        MonitorDescriptor {
+            device_name: device_name.to_owned(),
+            device_path: String::new(),
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    fn grid(rows: usize, columns: usize) -> GridConfig {
+        GridConfig {
+            rows,
+            columns,
+            ..GridConfig::default()
+        }
+    }
+
+    fn backups(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("bak"))
+            .collect()
+    }
+
+    #[test]
+    fn migrates_version_1() {
+        // As written by versions of grout before the cache was versioned
+        let contents = format!(
+            r#"{{(monitor:"\\\\.\\DISPLAY1{}",profile:"Default"):(rows:3,columns:4)}}"#,
+            r"\0".repeat(20)
+        );
+
+        let mut grids = parse_cache(&contents).unwrap();
+        let laptop = monitor(r"\\.\DISPLAY1");
+
+        assert!(migrate_keys(&mut grids, std::slice::from_ref(&laptop)));
+        assert_eq!(grids.len(), 1);
+        assert_eq!(grids[&GridConfigKey::new(&laptop, "Default")], grid(3, 4));
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(parse_cache("(version: 3, grids: {})").is_err());
+        assert!(parse_cache("(version: 0, grids: {})").is_err());
+        assert!(parse_cache("(version: 2, grids: {})").unwrap().is_empty());
+        assert!(parse_cache("not ron").is_err());
+    }
+
+    #[test]
+    fn round_trips() {
+        let dir = TempDir::new().unwrap();
+        let key = GridConfigKey::new(&monitor(r"\\.\DISPLAY2"), "Work");
+
+        let mut grids = GridConfigs::new();
+        grids.insert(
+            key.clone(),
+            GridConfig {
+                row_weights: vec![1, 2],
+                column_weights: vec![3, 1, 1],
+                ..grid(2, 3)
+            },
+        );
+
+        write_cache(dir.path(), &grids).unwrap();
+
+        assert_eq!(read_cache(dir.path()), grids);
+        assert!(fs::read_to_string(dir.path().join(CACHE_FILE))
+            .unwrap()
+            .starts_with("(version:2,"));
+    }
+
+    #[test]
+    fn writes_atomically() {
+        let dir = TempDir::new().unwrap();
+        let key = GridConfigKey::new(&monitor(r"\\.\DISPLAY1"), "Default");
+
+        let mut grids = GridConfigs::new();
+        grids.insert(key.clone(), grid(2, 2));
+        write_cache(dir.path(), &grids).unwrap();
+
+        grids.insert(key, grid(4, 4));
+        write_cache(dir.path(), &grids).unwrap();
+
+        let files = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(files, vec![CACHE_FILE]);
+        assert_eq!(read_cache(dir.path()), grids);
+    }
+
+    #[test]
+    fn missing_cache_is_empty() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(read_cache(dir.path()).is_empty());
+        assert!(read_cache(&dir.path().join("missing")).is_empty());
+        assert!(backups(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn backs_up_unparsable_cache() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+
+        fs::write(&path, "{(monitor: ").unwrap();
+
+        assert!(read_cache(dir.path()).is_empty());
+        assert!(!path.exists());
+
+        let backups = backups(dir.path());
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{(monitor: ");
+    }
+
+    #[test]
+    fn backs_up_unreadable_cache() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE);
+
+        fs::write(&path, [0xff, 0xfe, 0x00, 0x7b]).unwrap();
+
+        assert!(read_cache(dir.path()).is_empty());
+        assert!(!path.exists());
+
+        let backups = backups(dir.path());
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read(&backups[0]).unwrap(), vec![0xff, 0xfe, 0x00, 0x7b]);
+    }
+
+    #[test]
+    fn reads_pending_changes() {
+        let dir = TempDir::new().unwrap();
+        let key = GridConfigKey::new(&monitor(r"\\.\DISPLAY1"), "Default");
+
+        let cache = GridCache::open(Some(dir.path().to_owned()), Duration::from_secs(60));
+
+        assert_eq!(cache.get(&key), None);
+
+        cache.insert(key.clone(), grid(3, 3));
+
+        assert_eq!(cache.get(&key), Some(grid(3, 3)));
+        assert!(!dir.path().join(CACHE_FILE).exists());
+
+        cache.insert(key.clone(), grid(3, 4));
+        cache.flush();
+
+        assert_eq!(read_cache(dir.path())[&key], grid(3, 4));
+    }
+
+    #[test]
+    fn debounces_writes() {
+        let dir = TempDir::new().unwrap();
+        let key = GridConfigKey::new(&monitor(r"\\.\DISPLAY1"), "Default");
+
+        let cache = GridCache::open(Some(dir.path().to_owned()), Duration::from_millis(50));
+
+        for columns in 1..5 {
+            cache.insert(key.clone(), grid(1, columns));
+        }
+
+        thread::sleep(Duration::from_millis(500));
+
+        assert_eq!(read_cache(dir.path())[&key], grid(1, 4));
+    }
+
+    #[test]
+    fn migrates_keys_of_connected_monitors() {
+        let dir = TempDir::new().unwrap();
+        let docked = MonitorDescriptor {
+            device_path: r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352".to_owned(),
+            ..monitor(r"\\.\DISPLAY2")
+        };
+
+        fs::write(
+            dir.path().join(CACHE_FILE),
+            r#"(version: 2, grids: {(monitor: "\\\\.\\DISPLAY2", profile: "Default"): (rows: 1, columns: 3)})"#,
+        )
+        .unwrap();
+
+        let cache = GridCache::open(Some(dir.path().to_owned()), Duration::from_secs(60));
+        let key = GridConfigKey::new(&docked, "Default");
+
+        assert_eq!(cache.get(&key), None);
+
+        cache.migrate_keys(&[docked]);
+        cache.flush();
+
+        assert_eq!(cache.get(&key), Some(grid(1, 3)));
+        assert_eq!(read_cache(dir.path())[&key], grid(1, 3));
+    }
+}
@@ -0,0 +1,838 @@
+//! Checks a config file for every problem at once, pointing at the line & column of
+//! each, for `grout --check-config`
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::result;
+
+use config::{FileFormat, Value};
+use regex::Regex;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::config::{file_format, Config, ProfileConfig, Zone};
+use crate::locate::{child_path, locate};
+use crate::rules::Rule;
+use crate::spacing::MonitorSpacing;
+use crate::theme::Theme;
+use crate::Result;
+
+/// What's expected instead of the untagged enums that serde names in its errors
+const UNTAGGED_EXPECTED: &[(&str, &str)] = &[
+    (
+        "RawLength",
+        "expected pixels such as 10 or a percentage such as \"2%\"",
+    ),
+    (
+        "RawPadding",
+        "expected a size, or sizes for some of top, right, bottom & left",
+    ),
+    (
+        "RuleZone",
+        "expected rows, columns & span of a grid, or the name of a zone",
+    ),
+];
+
+/// Text of the config file being checked, along with its format
+#[derive(Clone, Copy)]
+struct ConfigText<'a> {
+    text: &'a str,
+    format: FileFormat,
+}
+
+/// Problem found in a config file
+#[derive(Debug)]
+pub struct Problem {
+    pub message: String,
+    /// Line & column, starting at 1, if the problem could be traced back to the file
+    pub position: Option<(usize, usize)>,
+    /// How the problem might be fixed
+    pub suggestion: Option<String>,
+}
+
+impl Problem {
+    /// Problem as `path:line:column: message`, followed by the suggestion on its own line
+    pub fn report(&self, path: &Path) -> String {
+        let mut report = match self.position {
+            Some((line, column)) => {
+                format!("{}:{}:{}: {}", path.display(), line, column, self.message)
+            }
+            None => format!("{}: {}", path.display(), self.message),
+        };
+
+        if let Some(suggestion) = &self.suggestion {
+            report.push_str(&format!("\n    help: {}", suggestion));
+        }
+
+        report
+    }
+}
+
+/// Every problem in the config file at `path`. Fails only if the file can't be read.
+pub fn check_config(path: &Path) -> Result<Vec<Problem>> {
+    let text = fs::read_to_string(path)?;
+
+    Ok(check_text(ConfigText {
+        text: &text,
+        format: file_format(path),
+    }))
+}
+
+fn check_text(file: ConfigText) -> Vec<Problem> {
+    let mut settings = config::Config::default();
+
+    if let Err(e) = settings.merge(config::File::from_str(file.text, file.format)) {
+        return vec![parse_problem(&e.to_string())];
+    }
+
+    let settings = match settings.try_into::<HashMap<String, Value>>() {
+        Ok(settings) => settings,
+        Err(e) => {
+            return vec![Problem {
+                message: e.to_string(),
+                position: None,
+                suggestion: None,
+            }]
+        }
+    };
+
+    let mut problems = unknown_settings(file, &settings);
+    let mut malformed = vec![];
+
+    let mut keys = settings.keys().collect::<Vec<_>>();
+    keys.sort();
+
+    for key in keys {
+        malformed_settings(vec![key.clone()], &settings[key], &mut malformed);
+    }
+
+    // The rest of the config is validated without the settings that couldn't be read,
+    // removing the last items of a list first so the others keep their index
+    malformed.sort_by(|(a, _), (b, _)| sort_key(b).cmp(&sort_key(a)));
+
+    let mut valid = Value::new(None, settings);
+    for (path, _) in &malformed {
+        valid = without(&valid, path).unwrap_or(valid);
+    }
+
+    problems.extend(malformed.iter().map(|(path, error)| Problem {
+        message: format!("{}: {}", dotted(path), error),
+        position: position(file, path),
+        suggestion: None,
+    }));
+
+    let malformed = malformed
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+
+    match Config::deserialize(valid) {
+        Ok(config) => {
+            for invalid in config.invalid_settings() {
+                let path = original_path(&invalid.path, &malformed);
+
+                // Settings holding a value that couldn't be read can't be checked as a whole
+                if malformed
+                    .iter()
+                    .any(|malformed| malformed.starts_with(&path))
+                {
+                    continue;
+                }
+
+                // Messages start with the path, which changes once items before it are removed
+                let message = invalid.error.to_string();
+                let message = match message.strip_prefix(&dotted(&invalid.path)) {
+                    Some(rest) => dotted(&path) + rest,
+                    None => message,
+                };
+
+                problems.push(Problem {
+                    message,
+                    position: position(file, &path),
+                    suggestion: invalid.suggestion,
+                });
+            }
+        }
+        Err(e) => problems.push(Problem {
+            message: e.to_string(),
+            position: None,
+            suggestion: None,
+        }),
+    }
+
+    // Problems that couldn't be traced back to the file come last
+    problems.sort_by_key(|problem| problem.position.unwrap_or((usize::MAX, 0)));
+
+    problems
+}
+
+/// Syntax errors end in `at line 3 column 5`
+fn parse_problem(error: &str) -> Problem {
+    let re_position = Regex::new(r"^(?s)(.*) at line (\d+) column (\d+)$").unwrap();
+
+    match re_position.captures(error) {
+        Some(cap) => Problem {
+            message: cap[1].to_owned(),
+            position: Some((cap[2].parse().unwrap_or(1), cap[3].parse().unwrap_or(1))),
+            suggestion: None,
+        },
+        None => Problem {
+            message: error.to_owned(),
+            position: None,
+            suggestion: None,
+        },
+    }
+}
+
+/// Settings the config doesn't have, along with the one that was likely meant
+fn unknown_settings(file: ConfigText, settings: &HashMap<String, Value>) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut check = |path: &[&str], table: &HashMap<String, Value>, fields: &[&str]| {
+        for (key, _) in sorted(table) {
+            if fields.contains(&key.as_str()) {
+                continue;
+            }
+
+            let mut path = path.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+            path.push(key.clone());
+
+            problems.push(Problem {
+                message: format!("Unknown setting {}", dotted(&path)),
+                position: position(file, &path),
+                suggestion: closest(key, fields).map(|field| format!("did you mean {}?", field)),
+            });
+        }
+    };
+
+    check(&[], settings, field_names::<Config>());
+
+    if let Some(theme) = table(settings.get("theme")) {
+        check(&["theme"], &theme, field_names::<Theme>());
+    }
+
+    for (name, monitor) in tables(settings.get("monitors")) {
+        check(
+            &["monitors", &name],
+            &monitor,
+            field_names::<MonitorSpacing>(),
+        );
+    }
+
+    for (name, zones) in children(settings.get("layouts")) {
+        for (idx, zone) in tables(Some(&zones)) {
+            check(&["layouts", &name, &idx], &zone, field_names::<Zone>());
+        }
+    }
+
+    for (name, profile) in tables(settings.get("profiles")) {
+        check(
+            &["profiles", &name],
+            &profile,
+            field_names::<ProfileConfig>(),
+        );
+
+        if let Some(theme) = table(profile.get("theme")) {
+            check(
+                &["profiles", &name, "theme"],
+                &theme,
+                field_names::<Theme>(),
+            );
+        }
+    }
+
+    for (idx, rule) in tables(settings.get("rules")) {
+        check(&["rules", &idx], &rule, field_names::<Rule>());
+    }
+
+    problems
+}
+
+/// Settings under `path` that can't be read as the type of their setting, along with
+/// why, narrowed down to the innermost values that can't be read on their own
+fn malformed_settings(
+    path: Vec<String>,
+    value: &Value,
+    malformed: &mut Vec<(Vec<String>, String)>,
+) {
+    let error = match read_alone(&path, value) {
+        Ok(()) => return,
+        Err(error) => error,
+    };
+
+    let children = children(Some(value));
+
+    // Settings such as zones need all of their fields, so they're reported as a whole
+    if !children.is_empty() && read_alone(&path, &empty(value)).is_ok() {
+        let reported = malformed.len();
+
+        for (key, child) in children {
+            malformed_settings(child_path(&path, &key), &child, malformed);
+        }
+
+        if malformed.len() > reported {
+            return;
+        }
+    }
+
+    malformed.push((path, describe(&error)));
+}
+
+/// `value` without the setting at `path` below it, or `None` if `path` is empty
+fn without(value: &Value, path: &[String]) -> Option<Value> {
+    let (key, rest) = path.split_first()?;
+
+    if let Ok(mut items) = value.clone().into_array() {
+        match index(key).filter(|idx| *idx < items.len()) {
+            Some(idx) => match without(&items[idx], rest) {
+                Some(item) => items[idx] = item,
+                None => {
+                    items.remove(idx);
+                }
+            },
+            None => return Some(value.clone()),
+        }
+
+        return Some(Value::new(None, items));
+    }
+
+    let mut table = match table(Some(value)) {
+        Some(table) => table,
+        None => return Some(value.clone()),
+    };
+
+    match table.get(key).map(|child| without(child, rest)) {
+        Some(Some(child)) => {
+            table.insert(key.clone(), child);
+        }
+        Some(None) => {
+            table.remove(key);
+        }
+        None => {}
+    }
+
+    Some(Value::new(None, table))
+}
+
+/// Index of a list item written in brackets, such as 0 in `[0]`
+fn index(key: &str) -> Option<usize> {
+    key.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+}
+
+/// Path ordered with list items by their index rather than as text
+fn sort_key(path: &[String]) -> Vec<(Option<usize>, &str)> {
+    path.iter().map(|key| (index(key), key.as_str())).collect()
+}
+
+/// Path a setting had before the items of lists at `removed` were taken out
+fn original_path(path: &[String], removed: &[Vec<String>]) -> Vec<String> {
+    let mut original = vec![];
+
+    for key in path {
+        let key = match index(key) {
+            Some(idx) => (0..)
+                .map(|original_idx| format!("[{}]", original_idx))
+                .filter(|item| !removed.contains(&child_path(&original, item)))
+                .nth(idx)
+                .unwrap_or_else(|| key.clone()),
+            None => key.clone(),
+        };
+
+        original.push(key);
+    }
+
+    original
+}
+
+/// Empty list or table, the same as `value`
+fn empty(value: &Value) -> Value {
+    if value.clone().into_array().is_ok() {
+        Value::new(None, Vec::<Value>::new())
+    } else {
+        Value::new(None, HashMap::<String, Value>::new())
+    }
+}
+
+/// Reads a config with only the value at `path` set
+fn read_alone(path: &[String], value: &Value) -> result::Result<(), String> {
+    let nested = path.iter().rev().fold(value.clone(), |value, key| {
+        if key.starts_with('[') {
+            Value::new(None, vec![value])
+        } else {
+            let mut table = HashMap::new();
+            table.insert(key.clone(), value);
+
+            Value::new(None, table)
+        }
+    });
+
+    Config::deserialize(nested)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Error from serde without the key it adds, since the path is shown instead
+fn describe(error: &str) -> String {
+    let error = error.split(" for key `").next().unwrap_or(error);
+
+    UNTAGGED_EXPECTED
+        .iter()
+        .find(|(name, _)| error.ends_with(&format!("untagged enum {}", name)))
+        .map(|(_, expected)| format!("invalid value, {}", expected))
+        .unwrap_or_else(|| error.to_owned())
+}
+
+/// Path as written in messages, such as `profiles.Work.key` or `rules[0].zone`
+fn dotted(path: &[String]) -> String {
+    path.iter().fold(String::new(), |dotted, key| {
+        if dotted.is_empty() || key.starts_with('[') {
+            dotted + key
+        } else {
+            dotted + "." + key
+        }
+    })
+}
+
+fn table(value: Option<&Value>) -> Option<HashMap<String, Value>> {
+    value?.clone().into_table().ok()
+}
+
+/// Entries of a table by key, or items of a list along with their index in brackets
+fn children(value: Option<&Value>) -> Vec<(String, Value)> {
+    let value = match value {
+        Some(value) => value.clone(),
+        None => return vec![],
+    };
+
+    if let Ok(array) = value.clone().into_array() {
+        return array
+            .into_iter()
+            .enumerate()
+            .map(|(idx, item)| (format!("[{}]", idx), item))
+            .collect();
+    }
+
+    table(Some(&value))
+        .map(|table| {
+            sorted(&table)
+                .into_iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Children that are tables themselves
+fn tables(value: Option<&Value>) -> Vec<(String, HashMap<String, Value>)> {
+    children(value)
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, table(Some(&value))?)))
+        .collect()
+}
+
+fn sorted(table: &HashMap<String, Value>) -> Vec<(&String, &Value)> {
+    let mut entries = table.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| *key);
+
+    entries
+}
+
+/// Field most like `key`, ignoring case, if it's close enough to be a typo of it
+pub(crate) fn closest<'a>(key: &str, fields: &[&'a str]) -> Option<&'a str> {
+    let key = key.to_lowercase();
+
+    fields
+        .iter()
+        .map(|field| (edit_distance(&key, &field.to_lowercase()), *field))
+        .filter(|(distance, field)| *distance <= (field.len() / 4).max(2))
+        .min()
+        .map(|(_, field)| field)
+}
+
+/// Number of characters that have to be inserted, removed or replaced to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let replaced = diagonal + (a_char != *b_char) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = replaced.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Line & column of the setting at `path`. Stops at the last key that could be found,
+/// such as the profile when the profile doesn't have the key.
+fn position(file: ConfigText, path: &[String]) -> Option<(usize, usize)> {
+    let located = locate(file.text, file.format);
+
+    let (line, byte) = (1..=path.len()).rev().find_map(|len| {
+        located
+            .iter()
+            .find(|(located_path, _)| located_path[..] == path[..len])
+            .map(|(_, position)| *position)
+    })?;
+
+    let line_text = file.text.lines().nth(line).unwrap_or_default();
+
+    Some((line + 1, line_text[..byte].chars().count() + 1))
+}
+
+/// Names of the fields of `T`, which it asks for when deserializing itself
+fn field_names<T: for<'de> Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+
+    fields
+}
+
+/// Deserializer that only records the fields of the struct it's asked for
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de, 'a> Deserializer<'de> for FieldNames<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> result::Result<V::Value, Self::Error> {
+        Err(de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> result::Result<V::Value, Self::Error> {
+        *self.0 = fields;
+
+        Err(de::Error::custom("only the fields are needed"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, format: FileFormat) -> Vec<(Option<(usize, usize)>, String)> {
+        check_text(ConfigText { text, format })
+            .into_iter()
+            .map(|problem| (problem.position, problem.message))
+            .collect()
+    }
+
+    /// Line, column & start of the message of each problem
+    fn assert_problems(
+        problems: Vec<(Option<(usize, usize)>, String)>,
+        expected: &[((usize, usize), &str)],
+    ) {
+        assert_eq!(problems.len(), expected.len(), "{:#?}", problems);
+
+        for ((position, message), (expected_position, expected_message)) in
+            problems.iter().zip(expected)
+        {
+            assert_eq!(*position, Some(*expected_position), "{}", message);
+            assert!(message.starts_with(expected_message), "{}", message);
+        }
+    }
+
+    #[test]
+    fn finds_top_level_keys_after_nested_ones() {
+        let yaml = "\
+profiles:
+  Work:
+    hotkey: CTRL+ALT+W
+hotkey: CTRL+NOPE
+";
+        let json = r#"{
+  "profiles": { "Work": { "hotkey": "CTRL+ALT+W" } },
+  "hotkey": "CTRL+NOPE"
+}"#;
+
+        assert_problems(
+            check(yaml, FileFormat::Yaml),
+            &[((4, 1), "hotkey: Invalid hotkey <CTRL+NOPE>")],
+        );
+        assert_problems(
+            check(json, FileFormat::Json),
+            &[((3, 3), "hotkey: Invalid hotkey <CTRL+NOPE>")],
+        );
+    }
+
+    #[test]
+    fn finds_nested_keys_after_top_level_ones() {
+        let yaml = "\
+hotkey: CTRL+ALT+S
+profiles:
+  Work:
+    hotkey: CTRL+NOPE
+";
+        let json = r#"{
+  "hotkey": "CTRL+ALT+S",
+  "profiles": { "Work": { "hotkey": "CTRL+NOPE" } }
+}"#;
+        let toml = r#"
+hotkey = "CTRL+ALT+S"
+
+[profiles.Work]
+hotkey = "CTRL+NOPE"
+"#;
+
+        let message = "profiles.Work.hotkey: Invalid hotkey <CTRL+NOPE>";
+
+        assert_problems(check(yaml, FileFormat::Yaml), &[((4, 5), message)]);
+        assert_problems(check(json, FileFormat::Json), &[((3, 27), message)]);
+        assert_problems(check(toml, FileFormat::Toml), &[((5, 1), message)]);
+    }
+
+    #[test]
+    fn reports_hotkeys_used_twice() {
+        let yaml = "\
+hotkey_undo: CTRL+ALT+Z
+profiles:
+  Work:
+    hotkey: ctrl+alt+z
+zone_hotkeys:
+  ALT+CTRL+Z: { rows: 1, columns: 2, span: \"0,0\" }
+hotkey_fallbacks:
+  CTRL+ALT+Z: CTRL+ALT+S
+";
+
+        assert_problems(
+            check(yaml, FileFormat::Yaml),
+            &[
+                (
+                    (4, 5),
+                    "hotkey_undo and profiles.Work.hotkey both use <CTRL+ALT+Z>",
+                ),
+                (
+                    (6, 3),
+                    "hotkey_undo and zone_hotkeys.ALT+CTRL+Z both use <CTRL+ALT+Z>",
+                ),
+                (
+                    (8, 3),
+                    "hotkey_fallbacks.CTRL+ALT+Z: fallback <CTRL+ALT+S> is already used by hotkey",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_invalid_settings_along_with_malformed_ones() {
+        let yaml = "\
+profiles:
+  Work:
+    hotkey: CTRL+ALT+W
+    key: NOPE
+theme:
+  background: notacolor # not #rrggbb
+rules:
+  - exe: a.exe
+  - exe: b.exe
+    zone: { name: left }
+    profile: Nowhere
+hotkey: CTRL+NOPE
+";
+        let json = r##"{
+  "profiles": {
+    "Work": {
+      "hotkey": "CTRL+ALT+W",
+      "key": "NOPE"
+    }
+  },
+  "theme": { "background": "notacolor" },
+  "rules": [
+    { "exe": "a.exe" },
+    {
+      "exe": "b.exe",
+      "zone": { "name": "left" },
+      "profile": "Nowhere"
+    }
+  ],
+  "hotkey": "CTRL+NOPE"
+}"##;
+        let toml = r#"hotkey = "CTRL+NOPE"
+
+[profiles.Work]
+hotkey = "CTRL+ALT+W"
+key = "NOPE" # not a key
+
+[theme]
+background = "notacolor"
+
+[[rules]]
+exe = "a.exe"
+
+[[rules]]
+exe = "b.exe"
+zone = { name = "left" }
+profile = "Nowhere"
+"#;
+
+        let problems = |positions: [(usize, usize); 5]| {
+            let messages = [
+                "Profile Work: Unknown key NOPE",
+                "theme.background: Invalid color notacolor",
+                "rules[0]: missing field `zone`",
+                "rules[1]: profile Nowhere isn't defined",
+                "hotkey: Invalid hotkey <CTRL+NOPE>",
+            ];
+
+            positions
+                .iter()
+                .copied()
+                .zip(messages.iter().copied())
+                .collect::<Vec<_>>()
+        };
+
+        let mut toml_problems = problems([(5, 1), (8, 1), (10, 1), (13, 1), (1, 1)]);
+        toml_problems.sort();
+
+        assert_problems(
+            check(yaml, FileFormat::Yaml),
+            &problems([(4, 5), (6, 3), (8, 3), (9, 3), (12, 1)]),
+        );
+        assert_problems(
+            check(json, FileFormat::Json),
+            &problems([(5, 7), (8, 14), (10, 5), (11, 5), (17, 3)]),
+        );
+        assert_problems(check(toml, FileFormat::Toml), &toml_problems);
+    }
+
+    #[test]
+    fn skips_checks_of_settings_holding_malformed_values() {
+        let yaml = "\
+layouts:
+  Halves:
+    - { name: left, x: 0.0, y: 0.0, width: 0.5, height: 1.0 }
+    - { name: right, x: half, y: 0.0, width: 0.5, height: 1.0 }
+";
+
+        // The layout isn't reported as leaving a gap where the malformed zone is
+        assert_problems(
+            check(yaml, FileFormat::Yaml),
+            &[((4, 5), "layouts.Halves[1]: invalid type")],
+        );
+    }
+
+    #[test]
+    fn finds_items_of_inline_lists() {
+        let grid = "{ rows: 2, columns: 2, span: \"1,1\" }";
+        let yaml = format!(
+            "rules: [{{ exe: a.exe, zone: {} }}, {{ exe: b.exe, zone: {{ name: left }}, profile: Nowhere }}]",
+            grid
+        );
+        let toml = r#"rules = [
+  { exe = "a.exe", zone = { rows = 2, columns = 2, span = "1,1" } },
+  { exe = "b.exe", zone = { name = "left" }, profile = "Nowhere" },
+]
+"#;
+
+        let message = "rules[1]: profile Nowhere isn't defined";
+
+        assert_problems(check(&yaml, FileFormat::Yaml), &[((1, 69), message)]);
+        assert_problems(check(toml, FileFormat::Toml), &[((3, 3), message)]);
+    }
+
+    #[test]
+    fn suggests_settings_for_typos() {
+        let toml = "hotkeys = \"CTRL+ALT+S\"\n\n[theme]\nbackgruond = \"#000000\"\n";
+
+        let problems = check_text(ConfigText {
+            text: toml,
+            format: FileFormat::Toml,
+        });
+        let found = problems
+            .iter()
+            .map(|problem| {
+                (
+                    problem.position,
+                    problem.message.as_str(),
+                    problem.suggestion.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            found,
+            [
+                (
+                    Some((1, 1)),
+                    "Unknown setting hotkeys",
+                    Some("did you mean hotkey?")
+                ),
+                (
+                    Some((4, 1)),
+                    "Unknown setting theme.backgruond",
+                    Some("did you mean background?")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggests_names_for_typos() {
+        let yaml = "\
+hotkey: CTLR+ALT+S
+layouts:
+  Halves:
+    - { name: left, x: 0.0, y: 0.0, width: 0.5, height: 1.0 }
+    - { name: right, x: 0.5, y: 0.0, width: 0.5, height: 1.0 }
+profiles:
+  Work:
+    key: PGUPP
+    layout: Halfs
+rules:
+  - exe: a.exe
+    zone: { name: rigth }
+  - exe: b.exe
+    zone: { name: left }
+    profile: Wrok
+";
+
+        let suggestions = check_text(ConfigText {
+            text: yaml,
+            format: FileFormat::Yaml,
+        })
+        .into_iter()
+        .map(|problem| (problem.position, problem.suggestion))
+        .collect::<Vec<_>>();
+
+        let suggestion = |line, column, text: &str| (Some((line, column)), Some(text.to_owned()));
+
+        assert_eq!(
+            suggestions,
+            [
+                suggestion(1, 1, "did you mean <CTRL+ALT+S>?"),
+                suggestion(8, 5, "did you mean PGUP?"),
+                suggestion(9, 5, "did you mean Halves?"),
+                suggestion(11, 3, "did you mean right?"),
+                suggestion(13, 3, "did you mean Work?"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let problems = check_text(ConfigText {
+            text: "{\n  \"hotkey\": \"CTRL+ALT+S\",\n}",
+            format: FileFormat::Json,
+        });
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].position, Some((3, 1)));
+    }
+}
@@ -1,22 +1,96 @@
-use std::fs::{create_dir_all, write, File};
-use std::io::Read;
+//! Config file: reading it in any supported format, validating it and the settings it
+//! holds.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, create_dir_all, write};
+use std::path::{Path, PathBuf};
 
 use anyhow::format_err;
-use regex::{Captures, Regex};
+use config::FileFormat;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::Result;
+use crate::check::closest;
+use crate::geometry::fraction_area;
+use crate::keys::{key_names, Hotkey, Key};
+use crate::locate::key_position;
+use crate::monitor_id::MonitorDescriptor;
+use crate::rules::{Rule, RuleZone};
+use crate::spacing::{Length, MonitorSpacing, Padding, Spacing};
+use crate::theme::Theme;
+use crate::tiling::LAYOUTS;
+use crate::{Error, Rect, Result};
 
 static EXAMPLE_CONFIG: &str = "---
 # Example config file for Grout
 
-# Margin between windows, in pixels
+# Margin between windows, in pixels at 100% display scaling. Can also be a
+# percentage of the shorter side of the monitor, e.g. \"1%\"
 margins: 10
 
-# Padding between edge of monitor and windows, in pixels
+# Padding between edge of monitor and windows, in pixels at 100% display scaling or a
+# percentage of the monitor's width / height. Can be set per edge, edges left out get
+# no padding
 window_padding: 10
+#window_padding: { top: 10, right: 10, bottom: 10, left: 10 }
+
+# Margins & padding for a single monitor, by device name (DISPLAY1, DISPLAY2, ...) or
+# by the monitor id from the grid cache
+#monitors:
+#  DISPLAY2:
+#    margins: 5
+#    window_padding: { top: 10, right: \"15%\", bottom: 10, left: 10 }
+
+# Relative sizes of the grid columns / rows. Only applied when the grid has the same
+# number of columns / rows as there are weights, e.g. a wide center column on an ultrawide
+#column_weights: [1, 2, 1]
+#row_weights: [1, 1]
+
+# Custom zone layouts, shown in the grid instead of rows / columns. Zones are fractions of the
+# work area and must cover it completely without overlapping
+#layouts:
+#  focus:
+#    - { name: left, x: 0.0, y: 0.0, width: 0.25, height: 1.0 }
+#    - { name: center, x: 0.25, y: 0.0, width: 0.5, height: 1.0 }
+#    - { name: right, x: 0.75, y: 0.0, width: 0.25, height: 1.0 }
+
+# Layout to use for each profile, by name. F1 - F6 in the grid switch to the profiles
+# Default & Profile2 - Profile6
+#profile_layouts:
+#  Profile2: focus
 
-# Hotkey to activate grid. Valid modifiers are CTRL, ALT, SHIFT, WIN
+# Colors of the grid & zone preview, as #rrggbb. Colors left out keep their default
+#theme:
+#  background: \"#2c2c2c\"
+#  tile: \"#b2b2b2\"
+#  tile_hovered: \"#006494\"
+#  tile_selected: \"#004d80\"
+#  tile_border: \"#000000\"
+#  preview: \"#004d80\"
+
+# Settings for each profile, used instead of the ones above while the profile is
+# active. Monitor overrides under monitors still apply on top of a profile's margins &
+# padding. quick_resize closes the grid after each placement, as hotkey_quick_resize does.
+# Profiles can have any name. key switches to the profile while the grid is open (any key
+# a hotkey can end in, replacing the F1 - F6 default for that key), hotkey switches to it
+# at any time
+#profiles:
+#  Coding:
+#    key: F7
+#    hotkey: CTRL+ALT+C
+#    margins: 0
+#    window_padding: 0
+#    layout: focus
+#    quick_resize: true
+#    theme:
+#      tile_selected: \"#8a2be2\"
+
+# Hotkey to activate grid. Hotkeys are one or more of the modifiers CTRL, ALT, SHIFT, WIN
+# followed by a key: a letter or digit, F1 - F24, NUMPAD0 - NUMPAD9, a named key such as
+# LEFT, SPACE, HOME, PLUS or OEM_1 - OEM_8, a character such as ; on the keyboard layout,
+# a scan code such as SC029 for the same physical key on every layout, or a virtual key
+# code such as VK7B
 hotkey: CTRL+ALT+S
 
 # Hotkey to activate grid for a quick resize. Grid will automatically close after resize operation.
@@ -25,93 +99,1258 @@ hotkey: CTRL+ALT+S
 # Hotkey to maximize / restore the active window
 #hotkey_maximize_toggle: CTRL+ALT+X
 
+# Rules that automatically place windows when they open. Windows are matched by exe, class
+# and / or title (a regular expression). The zone is either a span of grid cells given as
+# row,column:row,column, or the name of a zone in the profile's layout. Monitors start at 1
+#rules:
+#  - exe: code.exe
+#    zone: { rows: 1, columns: 3, span: '0,1' }
+#    monitor: 1
+#  - class: Chrome_WidgetWin_1
+#    title: '.*YouTube.*'
+#    zone: { name: right }
+#    profile: Profile2
+
+# Hotkeys that send the active window straight to a zone, without opening the grid.
+# Zones are given the same way as for rules. Pressing the hotkey again cycles a zone
+# against the left or right edge through 1/2, 1/3 and 2/3 of the width
+#zone_hotkeys:
+#  CTRL+ALT+NUMPAD4: { rows: 1, columns: 2, span: '0,0' }
+#  CTRL+ALT+NUMPAD6: { rows: 1, columns: 2, span: '0,1' }
+#  CTRL+ALT+NUMPAD5: { name: center }
+
+# Automatically tile all windows on each monitor. Layouts are master_stack, bsp and monocle
+auto_tile: false
+auto_tile_layout: master_stack
+
+# Share of the monitor width taken up by the master window in the master_stack layout
+master_ratio: 0.55
+
+# Hotkeys to toggle auto tiling, cycle its layout and resize the master window
+#hotkey_auto_tile_toggle: CTRL+ALT+T
+#hotkey_auto_tile_cycle: CTRL+ALT+L
+#hotkey_master_grow: CTRL+ALT+K
+#hotkey_master_shrink: CTRL+ALT+J
+
+# Hotkeys to save / restore the position of every window. Snapshots can also be
+# restored from the system tray
+#hotkey_snapshot_save: CTRL+ALT+F9
+#hotkey_snapshot_restore: CTRL+ALT+F10
+
+# Hotkeys to move the active window one tile left / right / up / down, keeping its
+# span. Windows at the edge of the grid continue onto the adjacent monitor
+#hotkey_move_left: CTRL+ALT+SHIFT+H
+#hotkey_move_right: CTRL+ALT+SHIFT+L
+#hotkey_move_up: CTRL+ALT+SHIFT+K
+#hotkey_move_down: CTRL+ALT+SHIFT+J
+
+# Hotkeys to swap the active window with the tiled window next to it
+#hotkey_swap_left: CTRL+ALT+WIN+H
+#hotkey_swap_right: CTRL+ALT+WIN+L
+#hotkey_swap_up: CTRL+ALT+WIN+K
+#hotkey_swap_down: CTRL+ALT+WIN+J
+
+# Hotkeys to rotate the tiled windows in the active window's row one position
+# right / left
+#hotkey_rotate_forward: CTRL+ALT+WIN+R
+#hotkey_rotate_backward: CTRL+ALT+WIN+E
+
+# Hotkeys to undo / redo window moves made with the grid, the maximize toggle or the
+# move hotkeys. Moves of all windows are undone from most to least recent
+#hotkey_undo: CTRL+ALT+Z
+#hotkey_redo: CTRL+ALT+Y
+
+# Windows placed in the same zone with the grid are stacked behind a strip of tabs.
+# Hotkeys to show the next / previous window of the active window's stack
+#hotkey_stack_next: CTRL+ALT+WIN+N
+#hotkey_stack_previous: CTRL+ALT+WIN+P
+
+# Hotkeys to use instead when a hotkey is already taken by another program. Hotkeys that
+# are taken without a fallback are disabled. The system tray lists which hotkeys are active
+#hotkey_fallbacks:
+#  CTRL+ALT+S: CTRL+ALT+WIN+S
+
 # Automatically launch program on startup
 auto_start: false
 ";
 
-pub fn load_config() -> Result<Config> {
-    let mut config_path =
+/// Config files looked for in the config directory, in order of precedence. The first
+/// one that exists is loaded. If none do, `config.yml` is created from the example config.
+pub const CONFIG_FILES: [&str; 3] = ["config.toml", "config.json", "config.yml"];
+
+/// Path of the config file in use, which may not exist yet
+pub fn config_path() -> Result<PathBuf> {
+    let mut config_dir =
         dirs::config_dir().ok_or_else(|| format_err!("Failed to get config directory"))?;
-    config_path.push("grout");
+    config_dir.push("grout");
 
-    if !config_path.exists() {
-        create_dir_all(&config_path)?;
+    let name = CONFIG_FILES
+        .iter()
+        .find(|name| config_dir.join(name).exists())
+        .unwrap_or(&CONFIG_FILES[CONFIG_FILES.len() - 1]);
+
+    Ok(config_dir.join(name))
+}
+
+/// Format of a config file, by its extension. Files with any other extension are YAML.
+pub(crate) fn file_format(path: &Path) -> FileFormat {
+    match path.extension().and_then(OsStr::to_str) {
+        Some("toml") => FileFormat::Toml,
+        Some("json") => FileFormat::Json,
+        _ => FileFormat::Yaml,
+    }
+}
+
+/// Reads & validates the config in use, see [`config_path`]. The example config is
+/// written to `config.yml` first if there is no config yet.
+pub fn load_config() -> Result<Config> {
+    let config_path = config_path()?;
+
+    if let Some(config_dir) = config_path.parent() {
+        if !config_dir.exists() {
+            create_dir_all(config_dir)?;
+        }
     }
 
-    config_path.push("config.yml");
     if !config_path.exists() {
         write(&config_path, EXAMPLE_CONFIG)?;
     }
 
+    let config_str = fs::read_to_string(&config_path)?;
+
+    let config = parse_config(&config_str, file_format(&config_path))?;
+    config.validate()?;
+
+    Ok(config)
+}
+
+/// Config from the text of a config file, with the defaults for what it leaves out
+fn parse_config(config_str: &str, format: FileFormat) -> Result<Config> {
     let mut config = config::Config::default();
     config.merge(config::Config::try_from(&Config::default())?)?;
+    config.merge(config::File::from_str(config_str, format))?;
 
-    let file_config = config::File::from(config_path).format(config::FileFormat::Yaml);
-
-    let config = config.merge(file_config)?;
-    Ok(config.clone().try_into()?)
+    Ok(config.try_into()?)
 }
 
+/// Flips `auto_start` in the config file in use, keeping the rest of the file as is
 pub fn toggle_autostart() -> Result<()> {
-    let mut config_path =
-        dirs::config_dir().ok_or_else(|| format_err!("Failed to get config directory"))?;
-    config_path.push("grout");
-    config_path.push("config.yml");
+    let config_path = config_path()?;
+    let config_str = fs::read_to_string(&config_path)?;
 
-    let mut config = File::open(&config_path)?;
-    let mut config_str = String::new();
+    let updated_config = toggle_setting(&config_str, file_format(&config_path), "auto_start")?;
 
-    config.read_to_string(&mut config_str)?;
+    write(&config_path, updated_config)?;
 
-    let re_line = Regex::new(r"(?m)^(auto_start:)(.*)$")?;
-    let updated_config = if let Some(cap) = re_line.captures_iter(&config_str).next() {
-        if re_line.captures_len() == 3 {
-            let re_cap = Regex::new(r"(?m)^(y|Y|yes|Yes|YES|true|True|TRUE|on|On|ON)$")?;
+    Ok(())
+}
 
-            let enabled = re_cap.find(&cap[2].trim());
+/// Flips the top level setting `key` between `true` & `false`, or adds it as `true` if
+/// the config doesn't have it. Settings of the same name in tables are left alone.
+fn toggle_setting(config_str: &str, format: FileFormat, key: &str) -> Result<String> {
+    let (line_idx, byte) = match key_position(config_str, format, &[key.to_owned()]) {
+        Some(position) => position,
+        None => return Ok(add_setting(config_str, format, key)),
+    };
 
-            let updated_config = re_line.replace(&config_str, |caps: &Captures| {
-                format!("{} {}", &caps[1], !enabled.is_some())
-            });
+    let re_value = Regex::new(r#"^["']?\w+["']?\s*[:=]\s*(\w+)"#)?;
 
-            Some(updated_config.as_ref().to_owned())
-        } else {
-            None
+    let mut lines = config_str.split('\n').collect::<Vec<_>>();
+    let line = lines[line_idx];
+
+    let value = re_value
+        .captures(&line[byte..])
+        .and_then(|cap| cap.get(1))
+        .ok_or_else(|| format_err!("{} in the config isn't true or false", key))?;
+
+    let enabled = match value.as_str() {
+        "true" => true,
+        "false" => false,
+        // YAML reads these as booleans too
+        "y" | "Y" | "yes" | "Yes" | "YES" | "True" | "TRUE" | "on" | "On" | "ON"
+            if format == FileFormat::Yaml =>
+        {
+            true
         }
-    } else {
-        None
+        "n" | "N" | "no" | "No" | "NO" | "False" | "FALSE" | "off" | "Off" | "OFF"
+            if format == FileFormat::Yaml =>
+        {
+            false
+        }
+        _ => return Err(format_err!("{} in the config isn't true or false", key)),
     };
 
-    let updated_config = if let Some(updated_config) = updated_config {
-        updated_config
-    } else {
-        format!("{}\n\nauto_start: true", config_str)
-    };
+    let value = (byte + value.start())..(byte + value.end());
+    let updated_line = format!("{}{}{}", &line[..value.start], !enabled, &line[value.end..]);
+    lines[line_idx] = &updated_line;
 
-    write(&config_path, updated_config)?;
+    Ok(lines.join("\n"))
+}
 
-    Ok(())
+/// Config with the top level setting `key` added as `true`
+fn add_setting(config_str: &str, format: FileFormat, key: &str) -> String {
+    match format {
+        // Settings after the first table header would belong to that table
+        FileFormat::Toml => format!("{} = true\n{}", key, config_str),
+        FileFormat::Json => {
+            let rest = config_str.trim_start().trim_start_matches('{');
+
+            if rest.trim_start().starts_with('}') {
+                format!("{{ \"{}\": true {}", key, rest.trim_start())
+            } else {
+                format!("{{\n  \"{}\": true,{}", key, rest)
+            }
+        }
+        _ => format!("{}\n\n{}: true", config_str, key),
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Settings read from the config file. Settings left out get the value of
+/// `Config::default`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+// Empty maps & lists don't survive merging the defaults into the config file
+#[serde(default)]
 pub struct Config {
-    pub margins: u8,
-    pub window_padding: u8,
+    pub margins: Length,
+    pub window_padding: Padding,
+    pub monitors: HashMap<String, MonitorSpacing>,
+    pub row_weights: Option<Vec<u32>>,
+    pub column_weights: Option<Vec<u32>>,
+    pub layouts: HashMap<String, Vec<Zone>>,
+    pub profile_layouts: HashMap<String, String>,
+    pub theme: Theme,
+    pub profiles: HashMap<String, ProfileConfig>,
+    pub rules: Vec<Rule>,
+    pub zone_hotkeys: HashMap<String, RuleZone>,
     pub hotkey: String,
     pub hotkey_quick_resize: Option<String>,
     pub hotkey_maximize_toggle: Option<String>,
+    pub auto_tile: bool,
+    pub auto_tile_layout: String,
+    pub master_ratio: f32,
+    pub hotkey_auto_tile_toggle: Option<String>,
+    pub hotkey_auto_tile_cycle: Option<String>,
+    pub hotkey_master_grow: Option<String>,
+    pub hotkey_master_shrink: Option<String>,
+    pub hotkey_snapshot_save: Option<String>,
+    pub hotkey_snapshot_restore: Option<String>,
+    pub hotkey_move_left: Option<String>,
+    pub hotkey_move_right: Option<String>,
+    pub hotkey_move_up: Option<String>,
+    pub hotkey_move_down: Option<String>,
+    pub hotkey_swap_left: Option<String>,
+    pub hotkey_swap_right: Option<String>,
+    pub hotkey_swap_up: Option<String>,
+    pub hotkey_swap_down: Option<String>,
+    pub hotkey_rotate_forward: Option<String>,
+    pub hotkey_rotate_backward: Option<String>,
+    pub hotkey_undo: Option<String>,
+    pub hotkey_redo: Option<String>,
+    pub hotkey_stack_next: Option<String>,
+    pub hotkey_stack_previous: Option<String>,
+    pub hotkey_fallbacks: HashMap<String, String>,
     pub auto_start: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            margins: 10,
-            window_padding: 10,
+            margins: Length::Pixels(10),
+            window_padding: Padding::uniform(Length::Pixels(10)),
+            monitors: HashMap::new(),
+            row_weights: None,
+            column_weights: None,
+            layouts: HashMap::new(),
+            profile_layouts: HashMap::new(),
+            theme: Theme::default(),
+            profiles: HashMap::new(),
+            rules: vec![],
+            zone_hotkeys: HashMap::new(),
             hotkey: "CTRL+ALT+S".to_string(),
             hotkey_quick_resize: None,
             hotkey_maximize_toggle: None,
+            auto_tile: false,
+            auto_tile_layout: "master_stack".to_string(),
+            master_ratio: 0.55,
+            hotkey_auto_tile_toggle: None,
+            hotkey_auto_tile_cycle: None,
+            hotkey_master_grow: None,
+            hotkey_master_shrink: None,
+            hotkey_snapshot_save: None,
+            hotkey_snapshot_restore: None,
+            hotkey_move_left: None,
+            hotkey_move_right: None,
+            hotkey_move_up: None,
+            hotkey_move_down: None,
+            hotkey_swap_left: None,
+            hotkey_swap_right: None,
+            hotkey_swap_up: None,
+            hotkey_swap_down: None,
+            hotkey_rotate_forward: None,
+            hotkey_rotate_backward: None,
+            hotkey_undo: None,
+            hotkey_redo: None,
+            hotkey_stack_next: None,
+            hotkey_stack_previous: None,
+            hotkey_fallbacks: HashMap::new(),
             auto_start: false,
         }
     }
 }
+
+impl Config {
+    /// Zone layout assigned to `profile`, if any
+    pub fn layout(&self, profile: &str) -> Option<&[Zone]> {
+        self.profiles
+            .get(profile)
+            .and_then(|settings| settings.layout.as_ref())
+            .or_else(|| self.profile_layouts.get(profile))
+            .and_then(|name| self.layouts.get(name))
+            .map(Vec::as_slice)
+    }
+
+    /// Theme of the grid while `profile` is active
+    pub fn theme(&self, profile: &str) -> Theme {
+        self.profiles
+            .get(profile)
+            .and_then(|settings| settings.theme)
+            .unwrap_or(self.theme)
+    }
+
+    /// Grid closes after each placement while `profile` is active
+    pub fn quick_resize(&self, profile: &str) -> bool {
+        matches!(self.profiles.get(profile), Some(settings) if settings.quick_resize)
+    }
+
+    /// Margins & padding on `monitor` while `profile` is active. Overrides for the
+    /// monitor under `monitors` take precedence over those of the profile.
+    pub fn spacing(&self, profile: &str, monitor: &MonitorDescriptor) -> Spacing {
+        let mut spacing = Spacing {
+            margins: self.margins,
+            padding: self.window_padding,
+        };
+
+        if let Some(settings) = self.profiles.get(profile) {
+            spacing.margins = settings.margins.unwrap_or(spacing.margins);
+            spacing.padding = settings.window_padding.unwrap_or(spacing.padding);
+        }
+
+        if let Some(overrides) = monitor.lookup(&self.monitors) {
+            spacing.margins = overrides.margins.unwrap_or(spacing.margins);
+            spacing.padding = overrides.window_padding.unwrap_or(spacing.padding);
+        }
+
+        spacing
+    }
+
+    /// Keys that switch profile while the grid is open, along with the profile each
+    /// switches to. F1 - F6 switch to Default & Profile2 - Profile6, unless a profile
+    /// under `profiles` takes the key.
+    pub fn profile_keys(&self) -> Vec<(String, String)> {
+        let mut keys: Vec<_> = DEFAULT_PROFILE_KEYS
+            .iter()
+            .map(|(key, profile)| (key.to_string(), profile.to_string()))
+            .collect();
+
+        for (profile, key) in self.sorted_profiles(|settings| settings.key.as_ref()) {
+            keys.retain(|(other, _)| !same_key(other, key));
+            keys.push((key.clone(), profile.clone()));
+        }
+
+        keys
+    }
+
+    /// Hotkeys that switch profile without opening the grid, along with the profile
+    /// each switches to
+    pub fn profile_hotkeys(&self) -> Vec<(String, String)> {
+        self.sorted_profiles(|settings| settings.hotkey.as_ref())
+            .into_iter()
+            .map(|(profile, hotkey)| (hotkey.clone(), profile.clone()))
+            .collect()
+    }
+
+    /// Name & setting of each profile that has the setting, by name
+    fn sorted_profiles<'a, T>(
+        &'a self,
+        setting: impl Fn(&'a ProfileConfig) -> Option<&'a T>,
+    ) -> Vec<(&'a String, &'a T)> {
+        let mut profiles: Vec<_> = self
+            .profiles
+            .iter()
+            .filter_map(|(profile, settings)| setting(settings).map(|value| (profile, value)))
+            .collect();
+
+        profiles.sort_by_key(|(profile, _)| *profile);
+
+        profiles
+    }
+
+    /// Area of each zone, by name, of the profile's custom layout laid out over the
+    /// `work_area` of `monitor`. Margins & padding are scaled to `dpi`.
+    pub fn layout_areas(
+        &self,
+        profile: &str,
+        monitor: &MonitorDescriptor,
+        work_area: Rect,
+        dpi: u32,
+    ) -> Option<Vec<(String, Rect)>> {
+        let spacing = self.spacing(profile, monitor);
+
+        let area = spacing.padded_area(work_area, dpi);
+        let margin = spacing.margin(work_area, dpi);
+
+        let areas = self
+            .layout(profile)?
+            .iter()
+            .map(|zone| (zone.name.clone(), fraction_area(area, zone, margin)))
+            .collect();
+
+        Some(areas)
+    }
+
+    /// Every hotkey in the config, along with the path of the setting it's from
+    fn hotkey_settings(&self) -> Vec<(Vec<String>, &str)> {
+        let mut settings = self.bound_hotkey_settings();
+
+        for (hotkey, fallback) in sorted(&self.hotkey_fallbacks) {
+            let path = setting_path(&["hotkey_fallbacks", hotkey]);
+
+            settings.push((path.clone(), hotkey.as_str()));
+            settings.push((path, fallback.as_str()));
+        }
+
+        settings
+    }
+
+    /// Hotkeys that get registered, leaving out fallbacks, along with the path of the
+    /// setting each is from
+    fn bound_hotkey_settings(&self) -> Vec<(Vec<String>, &str)> {
+        let optional = [
+            ("hotkey_quick_resize", &self.hotkey_quick_resize),
+            ("hotkey_maximize_toggle", &self.hotkey_maximize_toggle),
+            ("hotkey_auto_tile_toggle", &self.hotkey_auto_tile_toggle),
+            ("hotkey_auto_tile_cycle", &self.hotkey_auto_tile_cycle),
+            ("hotkey_master_grow", &self.hotkey_master_grow),
+            ("hotkey_master_shrink", &self.hotkey_master_shrink),
+            ("hotkey_snapshot_save", &self.hotkey_snapshot_save),
+            ("hotkey_snapshot_restore", &self.hotkey_snapshot_restore),
+            ("hotkey_move_left", &self.hotkey_move_left),
+            ("hotkey_move_right", &self.hotkey_move_right),
+            ("hotkey_move_up", &self.hotkey_move_up),
+            ("hotkey_move_down", &self.hotkey_move_down),
+            ("hotkey_swap_left", &self.hotkey_swap_left),
+            ("hotkey_swap_right", &self.hotkey_swap_right),
+            ("hotkey_swap_up", &self.hotkey_swap_up),
+            ("hotkey_swap_down", &self.hotkey_swap_down),
+            ("hotkey_rotate_forward", &self.hotkey_rotate_forward),
+            ("hotkey_rotate_backward", &self.hotkey_rotate_backward),
+            ("hotkey_undo", &self.hotkey_undo),
+            ("hotkey_redo", &self.hotkey_redo),
+            ("hotkey_stack_next", &self.hotkey_stack_next),
+            ("hotkey_stack_previous", &self.hotkey_stack_previous),
+        ];
+
+        let mut settings = vec![(setting_path(&["hotkey"]), self.hotkey.as_str())];
+
+        settings.extend(
+            optional
+                .iter()
+                .filter_map(|(name, hotkey)| Some((setting_path(&[name]), hotkey.as_deref()?))),
+        );
+
+        for (profile, hotkey) in self.sorted_profiles(|settings| settings.hotkey.as_ref()) {
+            settings.push((
+                setting_path(&["profiles", profile, "hotkey"]),
+                hotkey.as_str(),
+            ));
+        }
+
+        for (hotkey, _) in sorted(&self.zone_hotkeys) {
+            settings.push((setting_path(&["zone_hotkeys", hotkey]), hotkey.as_str()));
+        }
+
+        settings
+    }
+
+    /// Hotkey to register instead of `hotkey` when it's already taken
+    pub fn fallback_hotkey(&self, hotkey: &str) -> Option<&str> {
+        let hotkey = hotkey.parse::<Hotkey>().ok()?;
+
+        self.hotkey_fallbacks
+            .iter()
+            .find(|(other, _)| other.parse::<Hotkey>().ok() == Some(hotkey))
+            .map(|(_, fallback)| fallback.as_str())
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self.invalid_settings().into_iter().next() {
+            Some(invalid) => Err(invalid.error),
+            None => Ok(()),
+        }
+    }
+
+    /// Every setting that's out of range, malformed or refers to something that isn't
+    /// defined
+    pub fn invalid_settings(&self) -> Vec<InvalidSetting> {
+        let mut invalid = vec![];
+        let mut report = |path: &[&str], error: Error, suggestion: Option<String>| {
+            invalid.push(InvalidSetting {
+                path: setting_path(path),
+                error,
+                suggestion,
+            })
+        };
+
+        self.margins
+            .validate()
+            .unwrap_or_else(|e| report(&["margins"], format_err!("margins: {}", e), None));
+        self.window_padding.validate().unwrap_or_else(|e| {
+            report(
+                &["window_padding"],
+                format_err!("window_padding: {}", e),
+                None,
+            )
+        });
+
+        for (name, spacing) in sorted(&self.monitors) {
+            spacing.validate().unwrap_or_else(|e| {
+                report(
+                    &["monitors", name],
+                    format_err!("Monitor {}: {}", name, e),
+                    None,
+                )
+            });
+        }
+
+        for (name, zones) in sorted(&self.layouts) {
+            validate_layout(name, zones).unwrap_or_else(|e| report(&["layouts", name], e, None));
+        }
+
+        for (profile, layout) in sorted(&self.profile_layouts) {
+            if !self.layouts.contains_key(layout) {
+                report(
+                    &["profile_layouts", profile],
+                    undefined_layout(profile, layout),
+                    self.closest_layout(layout),
+                );
+            }
+        }
+
+        for (profile, settings) in sorted(&self.profiles) {
+            if let Some(key) = &settings.key {
+                if let Err(e) = key.parse::<Key>() {
+                    report(
+                        &["profiles", profile, "key"],
+                        format_err!("Profile {}: {}", profile, e),
+                        did_you_mean(
+                            closest_keys(key, |key| key.parse::<Key>().is_ok()).as_deref(),
+                        ),
+                    );
+                }
+            }
+
+            settings.validate().unwrap_or_else(|e| {
+                report(
+                    &["profiles", profile],
+                    format_err!("Profile {}: {}", profile, e),
+                    None,
+                )
+            });
+
+            if let Some(layout) = &settings.layout {
+                if !self.layouts.contains_key(layout) {
+                    report(
+                        &["profiles", profile, "layout"],
+                        undefined_layout(profile, layout),
+                        self.closest_layout(layout),
+                    );
+                }
+            }
+        }
+
+        let keys = self.sorted_profiles(|settings| settings.key.as_ref());
+
+        for (idx, (profile, key)) in keys.iter().enumerate() {
+            if let Some((other, _)) = keys[..idx]
+                .iter()
+                .find(|(_, other_key)| same_key(other_key, key))
+            {
+                report(
+                    &["profiles", profile, "key"],
+                    format_err!("Profiles {} and {} both use key {}", other, profile, key),
+                    None,
+                );
+            }
+        }
+
+        for (idx, rule) in self.rules.iter().enumerate() {
+            let item = format!("[{}]", idx);
+            let path = ["rules", &item];
+
+            rule.validate()
+                .unwrap_or_else(|e| report(&path, format_err!("rules{}: {}", item, e), None));
+
+            if let Some(profile) = &rule.profile {
+                if !self.has_profile(profile) {
+                    report(
+                        &path,
+                        format_err!(
+                            "rules{}: profile {} isn't defined under profiles or profile_layouts",
+                            item,
+                            profile
+                        ),
+                        self.closest_profile(profile),
+                    );
+
+                    continue;
+                }
+            }
+
+            if let RuleZone::Named { name } = &rule.zone {
+                if !self.has_named_zone(name, rule.profile.as_deref()) {
+                    report(
+                        &path,
+                        format_err!(
+                            "rules{}: zone {} isn't defined in any layout used by the rule",
+                            item,
+                            name
+                        ),
+                        self.closest_zone(name, rule.profile.as_deref()),
+                    );
+                }
+            }
+        }
+
+        for (hotkey, zone) in sorted(&self.zone_hotkeys) {
+            let path = ["zone_hotkeys", hotkey.as_str()];
+
+            zone.validate().unwrap_or_else(|e| {
+                report(&path, format_err!("Zone hotkey {}: {}", hotkey, e), None)
+            });
+
+            if let RuleZone::Named { name } = zone {
+                if !self.has_named_zone(name, None) {
+                    report(
+                        &path,
+                        format_err!(
+                            "Zone hotkey {}: zone {} isn't defined in any layout",
+                            hotkey,
+                            name
+                        ),
+                        self.closest_zone(name, None),
+                    );
+                }
+            }
+        }
+
+        if !LAYOUTS.contains(&self.auto_tile_layout.as_str()) {
+            report(
+                &["auto_tile_layout"],
+                format_err!(
+                    "Unknown auto_tile_layout {}, valid layouts are {}",
+                    self.auto_tile_layout,
+                    LAYOUTS.join(", ")
+                ),
+                did_you_mean(closest(&self.auto_tile_layout, &LAYOUTS)),
+            );
+        }
+
+        for (path, hotkey) in self.hotkey_settings() {
+            if let Err(e) = hotkey.parse::<Hotkey>() {
+                invalid.push(InvalidSetting {
+                    error: format_err!("{}: {}", path.join("."), e),
+                    path,
+                    suggestion: closest_keys(&hotkey, |hotkey| hotkey.parse::<Hotkey>().is_ok())
+                        .map(|hotkey| format!("did you mean <{}>?", hotkey)),
+                });
+            }
+        }
+
+        invalid.extend(self.duplicate_hotkeys());
+
+        invalid
+    }
+
+    /// Settings whose hotkey an earlier setting already uses. Only one of them could be
+    /// registered, and the other would be reported as taken by another program.
+    fn duplicate_hotkeys(&self) -> Vec<InvalidSetting> {
+        let bound = self
+            .bound_hotkey_settings()
+            .into_iter()
+            .filter_map(|(path, hotkey)| Some((path, hotkey.parse::<Hotkey>().ok()?)))
+            .collect::<Vec<_>>();
+
+        let used_by = |hotkey: &Hotkey, before: usize| {
+            bound[..before]
+                .iter()
+                .find(|(_, other)| other == hotkey)
+                .map(|(path, _)| path.join("."))
+        };
+
+        let mut duplicates = vec![];
+
+        for (idx, (path, hotkey)) in bound.iter().enumerate() {
+            if let Some(other) = used_by(hotkey, idx) {
+                duplicates.push(InvalidSetting {
+                    error: format_err!("{} and {} both use <{}>", other, path.join("."), hotkey),
+                    path: path.clone(),
+                    suggestion: None,
+                });
+            }
+        }
+
+        for (hotkey, fallback) in sorted(&self.hotkey_fallbacks) {
+            let other = fallback
+                .parse::<Hotkey>()
+                .ok()
+                .and_then(|fallback| used_by(&fallback, bound.len()));
+
+            if let Some(other) = other {
+                duplicates.push(InvalidSetting {
+                    error: format_err!(
+                        "hotkey_fallbacks.{}: fallback <{}> is already used by {}",
+                        hotkey,
+                        fallback.trim(),
+                        other
+                    ),
+                    path: setting_path(&["hotkey_fallbacks", hotkey]),
+                    suggestion: None,
+                });
+            }
+        }
+
+        duplicates
+    }
+
+    /// Profile has settings in the config or is one of the profiles switched to by default
+    fn has_profile(&self, profile: &str) -> bool {
+        self.profiles.contains_key(profile)
+            || self.profile_layouts.contains_key(profile)
+            || DEFAULT_PROFILE_KEYS
+                .iter()
+                .any(|(_, default_profile)| *default_profile == profile)
+    }
+
+    /// Zone is in the layout of `profile`, or in any layout if no profile is given
+    fn has_named_zone(&self, name: &str, profile: Option<&str>) -> bool {
+        self.zone_names(profile).contains(&name)
+    }
+
+    /// Names of the zones in the layout of `profile`, or in any layout if no profile is
+    /// given
+    fn zone_names(&self, profile: Option<&str>) -> Vec<&str> {
+        let layouts = match profile {
+            Some(profile) => self.layout(profile).into_iter().collect::<Vec<_>>(),
+            None => self.layouts.values().map(Vec::as_slice).collect(),
+        };
+
+        layouts
+            .into_iter()
+            .flatten()
+            .map(|zone| zone.name.as_str())
+            .collect()
+    }
+
+    fn closest_zone(&self, name: &str, profile: Option<&str>) -> Option<String> {
+        did_you_mean(closest(name, &self.zone_names(profile)))
+    }
+
+    fn closest_layout(&self, name: &str) -> Option<String> {
+        let layouts = self.layouts.keys().map(String::as_str).collect::<Vec<_>>();
+
+        did_you_mean(closest(name, &layouts))
+    }
+
+    fn closest_profile(&self, name: &str) -> Option<String> {
+        let profiles = self
+            .profiles
+            .keys()
+            .chain(self.profile_layouts.keys())
+            .map(String::as_str)
+            .chain(DEFAULT_PROFILE_KEYS.iter().map(|(_, profile)| *profile))
+            .collect::<Vec<_>>();
+
+        did_you_mean(closest(name, &profiles))
+    }
+}
+
+/// Setting found invalid by [`Config::invalid_settings`]
+#[derive(Debug)]
+pub struct InvalidSetting {
+    /// Keys leading to the setting, such as `profiles`, `Work`, `key`. Items of a list
+    /// are given by their index in brackets, such as `[0]`.
+    pub path: Vec<String>,
+    pub error: Error,
+    /// How the setting might be fixed, such as the name of a layout when the one given
+    /// has a typo
+    pub suggestion: Option<String>,
+}
+
+fn setting_path(path: &[&str]) -> Vec<String> {
+    path.iter().map(|key| key.to_string()).collect()
+}
+
+/// Entries of the map, by key, so settings are reported in the same order every time
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| *key);
+
+    entries
+}
+
+fn did_you_mean(name: Option<&str>) -> Option<String> {
+    name.map(|name| format!("did you mean {}?", name))
+}
+
+/// `text` with each key & modifier that has no meaning replaced by the name most like
+/// it, if that makes it `valid`
+fn closest_keys(text: &str, valid: impl Fn(&str) -> bool) -> Option<String> {
+    let names = key_names();
+    let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let parts = text
+        .split('+')
+        .map(str::trim)
+        .map(|part| {
+            if part.is_empty() {
+                None
+            } else if part.parse::<Key>().is_ok() || names.contains(&&*part.to_uppercase()) {
+                Some(part)
+            } else {
+                closest(part, &names)
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let suggested = parts.join("+");
+
+    Some(suggested).filter(|suggested| valid(suggested))
+}
+
+fn undefined_layout(profile: &str, layout: &str) -> Error {
+    format_err!(
+        "Profile {} uses layout {}, which isn't defined under layouts",
+        profile,
+        layout
+    )
+}
+
+/// Keys that switch profile in the grid when no profile in the config takes the key
+const DEFAULT_PROFILE_KEYS: [(&str, &str); 6] = [
+    ("F1", "Default"),
+    ("F2", "Profile2"),
+    ("F3", "Profile3"),
+    ("F4", "Profile4"),
+    ("F5", "Profile5"),
+    ("F6", "Profile6"),
+];
+
+/// Settings of a profile, used instead of the ones for every profile while it's active
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Key that switches to the profile while the grid is open
+    pub key: Option<String>,
+    /// Hotkey that switches to the profile without opening the grid
+    pub hotkey: Option<String>,
+    pub margins: Option<Length>,
+    pub window_padding: Option<Padding>,
+    pub layout: Option<String>,
+    pub theme: Option<Theme>,
+    pub quick_resize: bool,
+}
+
+impl ProfileConfig {
+    /// Checks the spacing of the profile. Its key is checked on its own, so that it's
+    /// reported at the key.
+    fn validate(&self) -> Result<()> {
+        MonitorSpacing {
+            margins: self.margins,
+            window_padding: self.window_padding,
+        }
+        .validate()
+    }
+}
+
+/// Keys are the same key whatever way they're written, such as ESC & ESCAPE
+fn same_key(a: &str, b: &str) -> bool {
+    match (a.parse::<Key>(), b.parse::<Key>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// A named zone, expressed as fractions of the work area
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Zone {
+    fn area(&self) -> f32 {
+        self.width * self.height
+    }
+
+    fn overlap(&self, other: &Zone) -> f32 {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+
+        width.max(0.0) * height.max(0.0)
+    }
+}
+
+/// Tolerance for rounding errors in fractional zone definitions
+const ZONE_EPSILON: f32 = 0.0001;
+
+fn validate_layout(name: &str, zones: &[Zone]) -> Result<()> {
+    if zones.is_empty() {
+        return Err(format_err!("Layout {} has no zones", name));
+    }
+
+    for zone in zones {
+        if zone.x < 0.0
+            || zone.y < 0.0
+            || zone.width <= 0.0
+            || zone.height <= 0.0
+            || zone.x + zone.width > 1.0 + ZONE_EPSILON
+            || zone.y + zone.height > 1.0 + ZONE_EPSILON
+        {
+            return Err(format_err!(
+                "Layout {}: zone {} must be within the work area, between 0.0 and 1.0",
+                name,
+                zone.name
+            ));
+        }
+    }
+
+    for (idx, zone) in zones.iter().enumerate() {
+        for other in zones[idx + 1..].iter() {
+            if zone.overlap(other) > ZONE_EPSILON {
+                return Err(format_err!(
+                    "Layout {}: zones {} and {} overlap",
+                    name,
+                    zone.name,
+                    other.name
+                ));
+            }
+        }
+    }
+
+    let covered: f32 = zones.iter().map(Zone::area).sum();
+    if covered < 1.0 - ZONE_EPSILON {
+        return Err(format_err!(
+            "Layout {}: zones leave gaps, only {:.1}% of the work area is covered",
+            name,
+            covered * 100.0
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monitor_spacing_prefers_stable_id() {
+        let monitor = MonitorDescriptor {
+            device_name: r"\\.\DISPLAY2".to_owned(),
+            device_path: r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352".to_owned(),
+            width: 2560,
+            height: 1440,
+        };
+        let margins = |pixels| MonitorSpacing {
+            margins: Some(Length::Pixels(pixels)),
+            window_padding: None,
+        };
+
+        let mut config = Config::default();
+        config.monitors.insert("DISPLAY2".to_owned(), margins(4));
+        config.monitors.insert(monitor.id(), margins(20));
+        config.monitors.insert("DISPLAY1".to_owned(), margins(8));
+
+        assert_eq!(
+            config.spacing("Default", &monitor).margins,
+            Length::Pixels(20)
+        );
+        assert_eq!(
+            config.spacing("Default", &monitor).padding,
+            config.window_padding
+        );
+
+        config.monitors.remove(&monitor.id());
+        assert_eq!(
+            config.spacing("Default", &monitor).margins,
+            Length::Pixels(4)
+        );
+    }
+
+    const YAML: &str = r##"
+margins: "2%"
+window_padding: { top: 10, right: "5%", bottom: 10, left: 0 }
+monitors:
+  DISPLAY2:
+    margins: 5
+column_weights: [1, 2, 1]
+layouts:
+  halves:
+    - { name: left, x: 0.0, y: 0.0, width: 0.5, height: 1.0 }
+    - { name: right, x: 0.5, y: 0.0, width: 0.5, height: 1.0 }
+profile_layouts:
+  Profile2: halves
+theme:
+  background: "#101010"
+profiles:
+  Coding:
+    key: F7
+    hotkey: CTRL+ALT+C
+    margins: 0
+    layout: halves
+    quick_resize: true
+    theme:
+      tile_selected: "#8a2be2"
+rules:
+  - exe: code.exe
+    zone: { rows: 1, columns: 3, span: "0,1" }
+    monitor: 1
+  - title: ".*YouTube.*"
+    zone: { name: right }
+    profile: Profile2
+zone_hotkeys:
+  CTRL+ALT+NUMPAD4: { rows: 1, columns: 2, span: "0,0" }
+  CTRL+ALT+NUMPAD5: { name: left }
+hotkey: CTRL+ALT+G
+hotkey_undo: CTRL+ALT+Z
+auto_tile: true
+auto_tile_layout: bsp
+master_ratio: 0.6
+hotkey_fallbacks:
+  CTRL+ALT+G: CTRL+SHIFT+G
+auto_start: true
+"##;
+
+    const TOML: &str = r##"
+margins = "2%"
+window_padding = { top = 10, right = "5%", bottom = 10, left = 0 }
+column_weights = [1, 2, 1]
+hotkey = "CTRL+ALT+G"
+hotkey_undo = "CTRL+ALT+Z"
+auto_tile = true
+auto_tile_layout = "bsp"
+master_ratio = 0.6
+auto_start = true
+
+[monitors.DISPLAY2]
+margins = 5
+
+[layouts]
+halves = [
+  { name = "left", x = 0.0, y = 0.0, width = 0.5, height = 1.0 },
+  { name = "right", x = 0.5, y = 0.0, width = 0.5, height = 1.0 },
+]
+
+[profile_layouts]
+Profile2 = "halves"
+
+[theme]
+background = "#101010"
+
+[profiles.Coding]
+key = "F7"
+hotkey = "CTRL+ALT+C"
+margins = 0
+layout = "halves"
+quick_resize = true
+
+[profiles.Coding.theme]
+tile_selected = "#8a2be2"
+
+[[rules]]
+exe = "code.exe"
+zone = { rows = 1, columns = 3, span = "0,1" }
+monitor = 1
+
+[[rules]]
+title = ".*YouTube.*"
+zone = { name = "right" }
+profile = "Profile2"
+
+[zone_hotkeys]
+"CTRL+ALT+NUMPAD4" = { rows = 1, columns = 2, span = "0,0" }
+"CTRL+ALT+NUMPAD5" = { name = "left" }
+
+[hotkey_fallbacks]
+"CTRL+ALT+G" = "CTRL+SHIFT+G"
+"##;
+
+    const JSON: &str = r##"{
+  "margins": "2%",
+  "window_padding": { "top": 10, "right": "5%", "bottom": 10, "left": 0 },
+  "monitors": { "DISPLAY2": { "margins": 5 } },
+  "column_weights": [1, 2, 1],
+  "layouts": {
+    "halves": [
+      { "name": "left", "x": 0.0, "y": 0.0, "width": 0.5, "height": 1.0 },
+      { "name": "right", "x": 0.5, "y": 0.0, "width": 0.5, "height": 1.0 }
+    ]
+  },
+  "profile_layouts": { "Profile2": "halves" },
+  "theme": { "background": "#101010" },
+  "profiles": {
+    "Coding": {
+      "key": "F7",
+      "hotkey": "CTRL+ALT+C",
+      "margins": 0,
+      "layout": "halves",
+      "quick_resize": true,
+      "theme": { "tile_selected": "#8a2be2" }
+    }
+  },
+  "rules": [
+    { "exe": "code.exe", "zone": { "rows": 1, "columns": 3, "span": "0,1" }, "monitor": 1 },
+    { "title": ".*YouTube.*", "zone": { "name": "right" }, "profile": "Profile2" }
+  ],
+  "zone_hotkeys": {
+    "CTRL+ALT+NUMPAD4": { "rows": 1, "columns": 2, "span": "0,0" },
+    "CTRL+ALT+NUMPAD5": { "name": "left" }
+  },
+  "hotkey": "CTRL+ALT+G",
+  "hotkey_undo": "CTRL+ALT+Z",
+  "auto_tile": true,
+  "auto_tile_layout": "bsp",
+  "master_ratio": 0.6,
+  "hotkey_fallbacks": { "CTRL+ALT+G": "CTRL+SHIFT+G" },
+  "auto_start": true
+}"##;
+
+    #[test]
+    fn formats_give_the_same_config() {
+        let yaml = parse_config(YAML, FileFormat::Yaml).unwrap();
+        let toml = parse_config(TOML, FileFormat::Toml).unwrap();
+        let json = parse_config(JSON, FileFormat::Json).unwrap();
+
+        assert_eq!(yaml, toml);
+        assert_eq!(yaml, json);
+
+        assert!(yaml.invalid_settings().is_empty());
+        assert_eq!(yaml.margins, Length::Percent(2.0));
+        assert_eq!(yaml.rules.len(), 2);
+        assert_eq!(yaml.profiles["Coding"].key.as_deref(), Some("F7"));
+        assert_eq!(yaml.fallback_hotkey("CTRL+ALT+G"), Some("CTRL+SHIFT+G"));
+        assert!(yaml.auto_start);
+    }
+
+    #[test]
+    fn reports_padding_wider_than_the_monitor() {
+        let yaml = "\
+monitors:
+  DELL:
+    window_padding: { left: \"70%\", right: \"40%\" }
+profiles:
+  Work:
+    window_padding: { top: \"50%\", bottom: \"50%\" }
+";
+        let config = parse_config(yaml, FileFormat::Yaml).unwrap();
+
+        let invalid = config
+            .invalid_settings()
+            .into_iter()
+            .map(|invalid| (invalid.path.join("."), invalid.error.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            invalid,
+            [(
+                "monitors.DELL".to_owned(),
+                "Monitor DELL: window_padding: left 70% and right 40% add up to more than 100%"
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn example_config_is_the_default() {
+        let example = parse_config(EXAMPLE_CONFIG, FileFormat::Yaml).unwrap();
+
+        assert_eq!(example, Config::default());
+    }
+
+    fn toggled(config_str: &str, format: FileFormat) -> String {
+        toggle_setting(config_str, format, "auto_start").unwrap()
+    }
+
+    #[test]
+    fn toggles_top_level_toml_setting() {
+        let toml = r#"# auto_start = false
+hotkey = "CTRL+ALT+S"
+auto_start=false   # started by hand
+
+[profiles.Work]
+auto_start = true
+"#;
+
+        assert_eq!(
+            toggled(toml, FileFormat::Toml),
+            toml.replace("auto_start=false", "auto_start=true")
+        );
+        assert_eq!(
+            toggled(&toggled(toml, FileFormat::Toml), FileFormat::Toml),
+            toml
+        );
+
+        let nested = "[theme]\nauto_start = true\n";
+        let added = toggled(nested, FileFormat::Toml);
+
+        assert_eq!(added, "auto_start = true\n[theme]\nauto_start = true\n");
+        assert!(parse_config(&added, FileFormat::Toml).unwrap().auto_start);
+    }
+
+    #[test]
+    fn toggles_top_level_json_setting() {
+        let json = r#"{
+  "profiles": { "Work": { "auto_start": false } },
+  "auto_start" :  true
+}"#;
+
+        assert_eq!(
+            toggled(json, FileFormat::Json),
+            json.replace(":  true", ":  false")
+        );
+
+        let added = toggled(
+            r#"{ "profiles": { "Work": { "auto_start": true } } }"#,
+            FileFormat::Json,
+        );
+        assert!(parse_config(&added, FileFormat::Json).unwrap().auto_start);
+
+        let added = toggled("{}", FileFormat::Json);
+        assert!(parse_config(&added, FileFormat::Json).unwrap().auto_start);
+    }
+
+    #[test]
+    fn toggles_top_level_yaml_setting() {
+        let yaml = "profiles:\n  Work:\n    auto_start: no\nauto_start: yes # started by hand\n";
+
+        assert_eq!(
+            toggled(yaml, FileFormat::Yaml),
+            "profiles:\n  Work:\n    auto_start: no\nauto_start: false # started by hand\n"
+        );
+
+        let added = toggled(EXAMPLE_CONFIG, FileFormat::Yaml);
+        assert!(parse_config(&added, FileFormat::Yaml).unwrap().auto_start);
+
+        let removed = toggled(&added, FileFormat::Yaml);
+        assert!(!parse_config(&removed, FileFormat::Yaml).unwrap().auto_start);
+    }
+
+    #[test]
+    fn toggle_rejects_settings_that_arent_booleans() {
+        assert!(toggle_setting("auto_start = \"maybe\"", FileFormat::Toml, "auto_start").is_err());
+        assert!(toggle_setting("auto_start: maybe", FileFormat::Yaml, "auto_start").is_err());
+    }
+}
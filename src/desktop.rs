@@ -0,0 +1,61 @@
+use grout::snapshot::{Snapshot, WindowSnapshot, WindowState};
+
+use crate::monitor::get_monitors;
+use crate::window::get_visible_windows;
+
+/// Captures every visible top-level window
+pub fn capture_snapshot() -> Snapshot {
+    let windows = get_visible_windows()
+        .into_iter()
+        .map(|window| {
+            let state = if window.is_minimized() {
+                WindowState::Minimized
+            } else if window.is_maximized() {
+                WindowState::Maximized
+            } else {
+                WindowState::Normal
+            };
+
+            WindowSnapshot {
+                exe: window.exe_name(),
+                title: window.title(),
+                monitor: window.monitor().descriptor().id(),
+                rect: window.normal_rect(),
+                state,
+            }
+        })
+        .collect();
+
+    Snapshot { windows }
+}
+
+/// Moves all open windows that match an entry of the snapshot back into place. Windows
+/// saved on a monitor that isn't connected anymore are left where they are.
+pub fn restore_snapshot(snapshot: &Snapshot) {
+    let monitors = get_monitors()
+        .into_iter()
+        .map(|monitor| monitor.descriptor())
+        .collect::<Vec<_>>();
+
+    let windows = get_visible_windows()
+        .into_iter()
+        .map(|window| (window, window.exe_name(), window.title()))
+        .collect::<Vec<_>>();
+
+    for (mut window, idx) in snapshot.match_windows(&windows) {
+        let entry = &snapshot.windows[idx];
+
+        if !entry.on_connected_monitor(&monitors) {
+            continue;
+        }
+
+        window.restore();
+        window.set_pos(entry.rect, None);
+
+        match entry.state {
+            WindowState::Maximized => window.maximize(),
+            WindowState::Minimized => window.minimize(),
+            WindowState::Normal => {}
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Scaling of sizes in the config, given at 100% display scaling, to a monitor's DPI.
+
+/// DPI of a monitor at 100% scaling
+pub const BASE_DPI: u32 = 96;
+
+/// Converts a value in logical units, as used in the config, to physical pixels
+/// on a monitor with the given DPI. Rounds to the nearest pixel.
+pub fn scale(value: i32, dpi: u32) -> i32 {
+    let scaled = value as i64 * dpi as i64;
+    let half = BASE_DPI as i64 / 2;
+
+    if scaled >= 0 {
+        ((scaled + half) / BASE_DPI as i64) as i32
+    } else {
+        ((scaled - half) / BASE_DPI as i64) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_dpi_is_unscaled() {
+        for value in [-50, -1, 0, 1, 7, 120, 1920].iter() {
+            assert_eq!(scale(*value, BASE_DPI), *value);
+        }
+    }
+
+    #[test]
+    fn scales_by_factor() {
+        assert_eq!(scale(10, 120), 13);
+        assert_eq!(scale(10, 144), 15);
+        assert_eq!(scale(10, 192), 20);
+        assert_eq!(scale(200, 288), 600);
+        assert_eq!(scale(10, 72), 8);
+    }
+
+    #[test]
+    fn rounds_to_nearest_pixel() {
+        // 1.25 & 1.75 pixels
+        assert_eq!(scale(1, 120), 1);
+        assert_eq!(scale(7, 24), 2);
+        // Halves round away from zero
+        assert_eq!(scale(1, 144), 2);
+        assert_eq!(scale(-1, 144), -2);
+        assert_eq!(scale(-10, 120), -13);
+    }
+
+    #[test]
+    fn large_values_dont_overflow() {
+        assert_eq!(scale(i32::MAX / 2, 192), i32::MAX - 1);
+    }
+}
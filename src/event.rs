@@ -1,7 +1,10 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs;
 use std::mem;
 use std::ptr;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use crossbeam_channel::{select, Receiver};
 
@@ -9,17 +12,33 @@ use winapi::shared::{
     minwindef::DWORD,
     windef::{HWINEVENTHOOK, HWND},
 };
-use winapi::um::winnt::LONG;
+use winapi::um::fileapi::{FindFirstChangeNotificationW, FindNextChangeNotification};
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::synchapi::WaitForSingleObject;
+use winapi::um::winbase::{INFINITE, WAIT_OBJECT_0};
+use winapi::um::winnt::{FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, LONG};
 use winapi::um::winuser::{
-    DispatchMessageW, PeekMessageW, SetWinEventHook, TranslateMessage, EVENT_SYSTEM_FOREGROUND,
+    DispatchMessageW, GetAncestor, GetMessageW, PeekMessageW, SetWinEventHook, TranslateMessage,
+    CHILDID_SELF, EVENT_OBJECT_DESTROY, EVENT_OBJECT_HIDE, EVENT_OBJECT_SHOW,
+    EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MOVESIZEEND, GA_ROOT, OBJID_WINDOW,
     WINEVENT_OUTOFCONTEXT,
 };
 
+use grout::config::config_path;
+
 use crate::common::get_active_monitor_name;
-use crate::window::Window;
+use crate::str_to_wide;
+use crate::window::{get_visible_windows, Window};
 use crate::Message;
 use crate::CHANNEL;
 
+thread_local! {
+    /// App windows the window hook has seen visible. Other processes show, hide &
+    /// destroy tooltips, menus & child windows all the time, so only these are worth
+    /// a refresh when they go away.
+    static APP_WINDOWS: RefCell<HashSet<HWND>> = RefCell::new(HashSet::new());
+}
+
 pub fn spawn_foreground_hook(close_msg: Receiver<()>) {
     thread::spawn(move || unsafe {
         SetWinEventHook(
@@ -47,6 +66,54 @@ pub fn spawn_foreground_hook(close_msg: Receiver<()>) {
     });
 }
 
+/// Notifies when top-level windows are shown, hidden, destroyed, focused or moved by
+/// the user, for as long as the program runs
+pub fn spawn_window_hook() {
+    thread::spawn(|| unsafe {
+        APP_WINDOWS.with(|windows| {
+            windows
+                .borrow_mut()
+                .extend(get_visible_windows().into_iter().map(|window| window.0))
+        });
+
+        SetWinEventHook(
+            EVENT_SYSTEM_FOREGROUND,
+            EVENT_SYSTEM_FOREGROUND,
+            ptr::null_mut(),
+            Some(window_callback),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+
+        SetWinEventHook(
+            EVENT_SYSTEM_MOVESIZEEND,
+            EVENT_SYSTEM_MOVESIZEEND,
+            ptr::null_mut(),
+            Some(window_callback),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+
+        SetWinEventHook(
+            EVENT_OBJECT_DESTROY,
+            EVENT_OBJECT_HIDE,
+            ptr::null_mut(),
+            Some(window_callback),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+
+        let mut msg = mem::zeroed();
+        while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) != 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
 pub fn spawn_track_monitor_thread(close_msg: Receiver<()>) {
     thread::spawn(move || unsafe {
         let sender = &CHANNEL.0.clone();
@@ -72,6 +139,63 @@ pub fn spawn_track_monitor_thread(close_msg: Receiver<()>) {
     });
 }
 
+/// Time given to editors that save the config file in several writes
+const CONFIG_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Notifies when the config file was saved, for as long as the program runs
+pub fn spawn_config_watcher() {
+    let config_dir = match config_path() {
+        Ok(path) => match path.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return,
+        },
+        Err(_) => return,
+    };
+
+    thread::spawn(move || unsafe {
+        let sender = &CHANNEL.0.clone();
+
+        // Windows can only watch directories, so changes to other files next to the
+        // config file are told apart by its modified time
+        let dir = str_to_wide!(config_dir.to_string_lossy());
+        let handle = FindFirstChangeNotificationW(
+            dir.as_ptr(),
+            0,
+            FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_FILE_NAME,
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            return;
+        }
+
+        let mut last_modified = config_modified();
+
+        while WaitForSingleObject(handle, INFINITE) == WAIT_OBJECT_0 {
+            thread::sleep(CONFIG_SETTLE_DELAY);
+
+            // Changes made while settling are reported by the next wait, but compare
+            // equal by then
+            if FindNextChangeNotification(handle) == 0 {
+                break;
+            }
+
+            let modified = config_modified();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            // The file is briefly missing while some editors replace it
+            if last_modified.is_some() {
+                let _ = sender.send(Message::ConfigChanged);
+            }
+        }
+    });
+}
+
+fn config_modified() -> Option<SystemTime> {
+    fs::metadata(config_path().ok()?).ok()?.modified().ok()
+}
+
 unsafe extern "system" fn callback(
     _hWinEventHook: HWINEVENTHOOK,
     _event: DWORD,
@@ -84,3 +208,53 @@ unsafe extern "system" fn callback(
     let sender = &CHANNEL.0.clone();
     let _ = sender.send(Message::ActiveWindowChange(Window(hwnd)));
 }
+
+unsafe extern "system" fn window_callback(
+    _hWinEventHook: HWINEVENTHOOK,
+    event: DWORD,
+    hwnd: HWND,
+    idObject: LONG,
+    idChild: LONG,
+    _idEventThread: DWORD,
+    _dwmsEventTime: DWORD,
+) {
+    if idObject != OBJID_WINDOW || idChild != CHILDID_SELF {
+        return;
+    }
+
+    let sender = &CHANNEL.0.clone();
+
+    // Hidden & destroyed windows can't be checked for being app windows anymore, so
+    // they're looked up among the ones seen before. Closed windows are cleaned up from
+    // the stacks & history.
+    if event == EVENT_OBJECT_DESTROY || event == EVENT_OBJECT_HIDE {
+        if APP_WINDOWS.with(|windows| windows.borrow_mut().remove(&hwnd)) {
+            let _ = sender.send(Message::WindowsChanged);
+        }
+        return;
+    }
+
+    if GetAncestor(hwnd, GA_ROOT) != hwnd {
+        return;
+    }
+
+    match event {
+        EVENT_SYSTEM_FOREGROUND => {
+            let _ = sender.send(Message::WindowFocused(Window(hwnd)));
+        }
+        EVENT_SYSTEM_MOVESIZEEND => {
+            let _ = sender.send(Message::WindowMoved);
+            return;
+        }
+        EVENT_OBJECT_SHOW => {
+            let _ = sender.send(Message::WindowShown(Window(hwnd)));
+        }
+        _ => return,
+    }
+
+    // Windows that get their title after being shown are picked up once focused
+    if Window(hwnd).is_app_window() && APP_WINDOWS.with(|windows| windows.borrow_mut().insert(hwnd))
+    {
+        let _ = sender.send(Message::WindowsChanged);
+    }
+}
@@ -0,0 +1,539 @@
+//! Pixel exact zone math of the grid & custom layouts, and moving between zones.
+
+use std::iter;
+
+use crate::config::Zone;
+use crate::Rect;
+
+/// Splits `length` into segments proportional to `weights`, with `gap` pixels
+/// between neighbouring segments. Returns the `(offset, size)` of each segment.
+///
+/// Segment boundaries are derived from the cumulative weight, so remainder pixels
+/// get spread across the segments instead of being dropped. The last segment
+/// always ends exactly at `length`.
+pub fn split(length: i32, weights: &[u32], gap: i32) -> Vec<(i32, i32)> {
+    if weights.is_empty() {
+        return vec![];
+    }
+
+    let gaps = gap as i64 * (weights.len() as i64 - 1);
+    let available = (length as i64 - gaps).max(0);
+
+    let total = weights
+        .iter()
+        .map(|weight| *weight as i64)
+        .sum::<i64>()
+        .max(1);
+
+    let mut cumulative = 0;
+    let mut start = 0;
+
+    weights
+        .iter()
+        .enumerate()
+        .map(|(idx, weight)| {
+            cumulative += *weight as i64;
+            let end = available * cumulative / total;
+
+            let segment = (
+                (start + idx as i64 * gap as i64) as i32,
+                (end - start) as i32,
+            );
+            start = end;
+
+            segment
+        })
+        .collect()
+}
+
+/// Direction to move or look for a zone in
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Top left & bottom right tiles, as `(row, column)`, of a span of grid tiles
+pub type Span = ((usize, usize), (usize, usize));
+
+/// Space along each edge of an area, in pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+impl Insets {
+    /// Same size along every edge
+    pub fn uniform(size: i32) -> Self {
+        Insets {
+            top: size,
+            right: size,
+            bottom: size,
+            left: size,
+        }
+    }
+
+    /// Part of `area` inside the insets
+    pub fn shrink(self, area: Rect) -> Rect {
+        Rect {
+            x: area.x + self.left,
+            y: area.y + self.top,
+            width: area.width - self.left - self.right,
+            height: area.height - self.top - self.bottom,
+        }
+    }
+}
+
+/// Zone math for a grid of weighted rows & columns laid out over an area
+pub struct GridGeometry<'a> {
+    pub area: Rect,
+    /// Space between the edges of `area` and the outer zones
+    pub padding: Insets,
+    /// Space between neighbouring zones
+    pub margin: i32,
+    pub row_weights: &'a [u32],
+    pub column_weights: &'a [u32],
+}
+
+impl<'a> GridGeometry<'a> {
+    fn columns(&self) -> Vec<(i32, i32)> {
+        split(
+            self.area.width - self.padding.left - self.padding.right,
+            self.column_weights,
+            self.margin,
+        )
+    }
+
+    fn rows(&self) -> Vec<(i32, i32)> {
+        split(
+            self.area.height - self.padding.top - self.padding.bottom,
+            self.row_weights,
+            self.margin,
+        )
+    }
+
+    /// Area of the tile at `row` & `column`
+    pub fn zone(&self, row: usize, column: usize) -> Rect {
+        let (x, width) = self.columns()[column];
+        let (y, height) = self.rows()[row];
+
+        Rect {
+            x: self.area.x + self.padding.left + x,
+            y: self.area.y + self.padding.top + y,
+            width,
+            height,
+        }
+    }
+
+    /// Area covering every zone between two tiles, inclusive
+    pub fn span(&self, from_tile: (usize, usize), to_tile: (usize, usize)) -> Rect {
+        let from_zone = self.zone(from_tile.0, from_tile.1);
+        let to_zone = self.zone(to_tile.0, to_tile.1);
+
+        Rect {
+            x: from_zone.x,
+            y: from_zone.y,
+            width: (to_zone.x + to_zone.width) - from_zone.x,
+            height: (to_zone.y + to_zone.height) - from_zone.y,
+        }
+    }
+
+    /// Span of tiles whose edges are closest to the edges of `rect`
+    pub fn locate(&self, rect: Rect) -> Span {
+        let origin_x = self.area.x + self.padding.left;
+        let origin_y = self.area.y + self.padding.top;
+
+        let (left, right) = nearest_segments(&self.columns(), rect.x - origin_x, rect.width);
+        let (top, bottom) = nearest_segments(&self.rows(), rect.y - origin_y, rect.height);
+
+        ((top, left), (bottom, right))
+    }
+
+    /// Span of tiles `rect` fills, if all its edges are within `tolerance` pixels
+    /// of the span's edges
+    pub fn snapped(&self, rect: Rect, tolerance: i32) -> Option<Span> {
+        let (from_tile, to_tile) = self.locate(rect);
+        let area = self.span(from_tile, to_tile);
+
+        let fits = (area.x - rect.x).abs() <= tolerance
+            && (area.y - rect.y).abs() <= tolerance
+            && (area.x + area.width - rect.x - rect.width).abs() <= tolerance
+            && (area.y + area.height - rect.y - rect.height).abs() <= tolerance;
+
+        if fits {
+            Some((from_tile, to_tile))
+        } else {
+            None
+        }
+    }
+
+    /// Area covering every tile, i.e. `area` inside the padding
+    pub fn max_area(&self) -> Rect {
+        self.span(
+            (0, 0),
+            (self.row_weights.len() - 1, self.column_weights.len() - 1),
+        )
+    }
+}
+
+/// Maps a zone's fractions onto `area`. Edges shared with other zones are inset so
+/// neighbouring zones end up `margin` apart.
+pub fn fraction_area(area: Rect, zone: &Zone, margin: i32) -> Rect {
+    let scale_x = |fraction: f32| area.x + (fraction * area.width as f32).round() as i32;
+    let scale_y = |fraction: f32| area.y + (fraction * area.height as f32).round() as i32;
+
+    let mut left = scale_x(zone.x);
+    let mut top = scale_y(zone.y);
+    let mut right = scale_x(zone.x + zone.width);
+    let mut bottom = scale_y(zone.y + zone.height);
+
+    if left > area.x {
+        left += margin - margin / 2;
+    }
+    if top > area.y {
+        top += margin - margin / 2;
+    }
+    if right < area.x + area.width {
+        right -= margin / 2;
+    }
+    if bottom < area.y + area.height {
+        bottom -= margin / 2;
+    }
+
+    Rect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    }
+}
+
+/// Indices of the segments whose start is closest to `offset` and whose end is
+/// closest to `offset + size`
+fn nearest_segments(segments: &[(i32, i32)], offset: i32, size: i32) -> (usize, usize) {
+    let nearest = |edge: &dyn Fn(&(i32, i32)) -> i32, target: i32| {
+        segments
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, segment)| (edge(segment) - target).abs())
+            .map(|(idx, _)| idx)
+            .unwrap_or_default()
+    };
+
+    let first = nearest(&|segment| segment.0, offset);
+    let last = nearest(&|segment| segment.0 + segment.1, offset + size);
+
+    (first, last.max(first))
+}
+
+/// Moves a span one tile in `direction` within a grid of `rows` x `columns`.
+/// Returns `None` if the span already touches that edge of the grid.
+pub fn step_span(span: Span, direction: Direction, rows: usize, columns: usize) -> Option<Span> {
+    let ((top, left), (bottom, right)) = span;
+
+    match direction {
+        Direction::Left if left > 0 => Some(((top, left - 1), (bottom, right - 1))),
+        Direction::Right if right + 1 < columns => Some(((top, left + 1), (bottom, right + 1))),
+        Direction::Up if top > 0 => Some(((top - 1, left), (bottom - 1, right))),
+        Direction::Down if bottom + 1 < rows => Some(((top + 1, left), (bottom + 1, right))),
+        _ => None,
+    }
+}
+
+/// Index of the closest span that shares rows (for left / right) or columns (for
+/// up / down) with `spans[from]` and lies in `direction` of it
+pub fn neighbour_span(spans: &[Span], from: usize, direction: Direction) -> Option<usize> {
+    let ((top, left), (bottom, right)) = spans[from];
+
+    spans
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx != from)
+        .filter_map(
+            |(idx, ((other_top, other_left), (other_bottom, other_right)))| {
+                let rows_shared = *other_top <= bottom && top <= *other_bottom;
+                let columns_shared = *other_left <= right && left <= *other_right;
+
+                let distance = match direction {
+                    Direction::Left if rows_shared && *other_right < left => left - other_right,
+                    Direction::Right if rows_shared && *other_left > right => other_left - right,
+                    Direction::Up if columns_shared && *other_bottom < top => top - other_bottom,
+                    Direction::Down if columns_shared && *other_top > bottom => other_top - bottom,
+                    _ => return None,
+                };
+
+                Some((idx, distance))
+            },
+        )
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(idx, _)| idx)
+}
+
+/// Where a span leaving another grid in `direction` lands in a grid of `rows` x
+/// `columns`: against the edge it entered through, keeping its size and position
+/// along that edge as far as the grid allows
+pub fn enter_span(span: Span, direction: Direction, rows: usize, columns: usize) -> Span {
+    let ((top, left), (bottom, right)) = span;
+
+    let height = (bottom - top).min(rows - 1);
+    let width = (right - left).min(columns - 1);
+
+    let top = top.min(rows - 1 - height);
+    let left = left.min(columns - 1 - width);
+
+    let (top, left) = match direction {
+        Direction::Left => (top, columns - 1 - width),
+        Direction::Right => (top, 0),
+        Direction::Up => (rows - 1 - height, left),
+        Direction::Down => (0, left),
+    };
+
+    ((top, left), (top + height, left + width))
+}
+
+/// Widths, as `(columns spanned, columns)`, that a zone against the left or right
+/// edge of the grid cycles through
+const CYCLE_WIDTHS: [(usize, usize); 3] = [(1, 2), (1, 3), (2, 3)];
+
+/// Width number `step` of the cycle for a zone spanning `span` in a grid of `columns`,
+/// such as on repeated presses of the zone's hotkey. The zone's own width comes first,
+/// followed by the halves & thirds it isn't, against the same edge as the zone. Zones
+/// against neither or both edges keep their width. Returns the span along with the
+/// number of columns of the grid it's in.
+pub fn cycle_width(span: Span, columns: usize, step: usize) -> (Span, usize) {
+    let ((top, left), (bottom, right)) = span;
+    let width = right - left + 1;
+
+    let against_left = left == 0;
+    let against_right = right + 1 == columns;
+
+    if against_left == against_right {
+        return (span, columns);
+    }
+
+    let widths = iter::once((width, columns))
+        .chain(
+            CYCLE_WIDTHS
+                .iter()
+                .copied()
+                .filter(|(other_width, other_columns)| {
+                    other_width * columns != width * other_columns
+                }),
+        )
+        .collect::<Vec<_>>();
+
+    let (width, columns) = widths[step % widths.len()];
+
+    let (left, right) = if against_left {
+        (0, width - 1)
+    } else {
+        (columns - width, columns - 1)
+    };
+
+    (((top, left), (bottom, right)), columns)
+}
+
+/// Index of the area that's next to `from` in `direction`, the closest one if
+/// several are
+pub fn adjacent_area(areas: &[Rect], from: Rect, direction: Direction) -> Option<usize> {
+    let overlaps = |start: i32, length: i32, other_start: i32, other_length: i32| {
+        start < other_start + other_length && other_start < start + length
+    };
+
+    areas
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, area)| {
+            let distance = match direction {
+                Direction::Left => from.x - (area.x + area.width),
+                Direction::Right => area.x - (from.x + from.width),
+                Direction::Up => from.y - (area.y + area.height),
+                Direction::Down => area.y - (from.y + from.height),
+            };
+
+            let aligned = match direction {
+                Direction::Left | Direction::Right => {
+                    overlaps(from.y, from.height, area.y, area.height)
+                }
+                Direction::Up | Direction::Down => overlaps(from.x, from.width, area.x, area.width),
+            };
+
+            if aligned && distance >= 0 {
+                Some((idx, distance))
+            } else {
+                None
+            }
+        })
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn weights() -> impl Strategy<Value = Vec<u32>> {
+        prop::collection::vec(1..5u32, 1..9)
+    }
+
+    fn geometry_parts() -> impl Strategy<Value = (Rect, Insets, i32, Vec<u32>, Vec<u32>)> {
+        (
+            (-3840..3840, -2160..2160, 800..3840, 600..2160),
+            (0..40, 0..40, 0..40, 0..40),
+            0..30,
+            weights(),
+            weights(),
+        )
+            .prop_map(
+                |((x, y, width, height), (top, right, bottom, left), margin, rows, columns)| {
+                    let area = Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                    };
+                    let padding = Insets {
+                        top,
+                        right,
+                        bottom,
+                        left,
+                    };
+
+                    (area, padding, margin, rows, columns)
+                },
+            )
+    }
+
+    #[test]
+    fn split_spreads_remainder() {
+        assert_eq!(
+            split(1920, &[1; 7], 0),
+            vec![
+                (0, 274),
+                (274, 274),
+                (548, 274),
+                (822, 275),
+                (1097, 274),
+                (1371, 274),
+                (1645, 275)
+            ]
+        );
+        assert_eq!(
+            split(1920, &[1; 7], 10),
+            vec![
+                (0, 265),
+                (275, 266),
+                (551, 266),
+                (827, 265),
+                (1102, 266),
+                (1378, 266),
+                (1654, 266)
+            ]
+        );
+        assert_eq!(split(100, &[1, 3], 0), vec![(0, 25), (25, 75)]);
+        assert_eq!(split(100, &[], 10), vec![]);
+    }
+
+    proptest! {
+        #[test]
+        fn split_fills_length(length in 0..5000, weights in weights(), gap in 0..30) {
+            let segments = split(length, &weights, gap);
+            let gaps = gap * (weights.len() as i32 - 1);
+
+            prop_assert_eq!(segments.len(), weights.len());
+            prop_assert_eq!(segments[0].0, 0);
+
+            for pair in segments.windows(2) {
+                prop_assert_eq!(pair[0].0 + pair[0].1 + gap, pair[1].0);
+            }
+
+            if length >= gaps {
+                let (offset, size) = segments[segments.len() - 1];
+                prop_assert_eq!(offset + size, length);
+            }
+        }
+
+        #[test]
+        fn zones_share_edges((area, padding, margin, rows, columns) in geometry_parts()) {
+            let geometry = GridGeometry {
+                area,
+                padding,
+                margin,
+                row_weights: &rows,
+                column_weights: &columns,
+            };
+
+            for row in 0..rows.len() {
+                for column in 0..columns.len() {
+                    let zone = geometry.zone(row, column);
+
+                    if column + 1 < columns.len() {
+                        let right = geometry.zone(row, column + 1);
+                        prop_assert_eq!(zone.x + zone.width + margin, right.x);
+                        prop_assert_eq!((zone.y, zone.height), (right.y, right.height));
+                    }
+
+                    if row + 1 < rows.len() {
+                        let below = geometry.zone(row + 1, column);
+                        prop_assert_eq!(zone.y + zone.height + margin, below.y);
+                        prop_assert_eq!((zone.x, zone.width), (below.x, below.width));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn zones_fill_max_area((area, padding, margin, rows, columns) in geometry_parts()) {
+            let geometry = GridGeometry {
+                area,
+                padding,
+                margin,
+                row_weights: &rows,
+                column_weights: &columns,
+            };
+
+            let max_area = geometry.max_area();
+            prop_assert_eq!(max_area, padding.shrink(area));
+
+            let first = geometry.zone(0, 0);
+            let last = geometry.zone(rows.len() - 1, columns.len() - 1);
+            prop_assert_eq!((first.x, first.y), (max_area.x, max_area.y));
+            prop_assert_eq!(last.x + last.width, max_area.x + max_area.width);
+            prop_assert_eq!(last.y + last.height, max_area.y + max_area.height);
+
+            let covered = (0..rows.len())
+                .flat_map(|row| (0..columns.len()).map(move |column| (row, column)))
+                .map(|(row, column)| {
+                    let zone = geometry.zone(row, column);
+                    zone.width as i64 * zone.height as i64
+                })
+                .sum::<i64>();
+            let margins = margin as i64 * (columns.len() as i64 - 1) * max_area.height as i64
+                + margin as i64 * (rows.len() as i64 - 1) * max_area.width as i64
+                - (margin as i64).pow(2) * (columns.len() as i64 - 1) * (rows.len() as i64 - 1);
+            prop_assert_eq!(covered + margins, max_area.width as i64 * max_area.height as i64);
+        }
+
+        #[test]
+        fn zones_are_located((area, padding, margin, rows, columns) in geometry_parts()) {
+            let geometry = GridGeometry {
+                area,
+                padding,
+                margin,
+                row_weights: &rows,
+                column_weights: &columns,
+            };
+
+            let last = (rows.len() - 1, columns.len() - 1);
+            prop_assert_eq!(geometry.locate(geometry.max_area()), ((0, 0), last));
+            prop_assert_eq!(geometry.snapped(geometry.span((0, 0), last), 0), Some(((0, 0), last)));
+        }
+    }
+}
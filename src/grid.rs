@@ -1,148 +1,170 @@
-use std::collections::HashMap;
-use std::fs;
 use std::mem;
-
-use serde::{Deserialize, Serialize};
+use std::ptr;
 
 use winapi::shared::windef::{HBRUSH, HDC};
-use winapi::um::wingdi::{CreateSolidBrush, DeleteObject, RGB};
-use winapi::um::winuser::{BeginPaint, EndPaint, FillRect, FrameRect, PAINTSTRUCT};
-
-use crate::common::{get_active_monitor_name, get_work_area, Rect};
-use crate::config::Config;
-use crate::window::Window;
-use crate::ACTIVE_PROFILE;
+use winapi::um::wingdi::{
+    CreateSolidBrush, DeleteObject, SetBkMode, SetTextColor, RGB, TRANSPARENT,
+};
+use winapi::um::winuser::{
+    BeginPaint, DrawTextW, EndPaint, FillRect, FrameRect, GetClientRect, InvalidateRect,
+    DT_END_ELLIPSIS, DT_LEFT, DT_NOPREFIX, DT_SINGLELINE, DT_VCENTER, PAINTSTRUCT,
+};
+
+use grout::config::{Config, Zone};
+use grout::dpi;
+use grout::geometry::{fraction_area, Direction, GridGeometry, Insets, Span};
+use grout::grid_config::{normalize_weights, GridConfig, GridConfigKey};
+use grout::monitor_id::MonitorDescriptor;
+use grout::spacing::Spacing;
+use grout::stack::Stacks;
+use grout::theme::Theme;
+use grout::Rect;
+
+use crate::common::{get_active_monitor_dpi, get_work_area};
+use crate::hotkey::key_vkcode;
+use crate::monitor::{get_active_monitor, get_monitors};
+use crate::str_to_wide;
+use crate::window::{get_visible_windows, Window};
+use crate::{ACTIVE_PROFILE, GRID_CACHE, HISTORY};
 
 const TILE_WIDTH: u32 = 48;
 const TILE_HEIGHT: u32 = 48;
+const LAYOUT_WIDTH: u32 = 192;
+
+/// Windows listed below the tiles, to pick the next window to place
+const LIST_ROW_HEIGHT: u32 = 24;
+const LIST_WIDTH: u32 = 240;
+const MAX_LIST_ROWS: usize = 8;
 
 pub struct Grid {
     pub shift_down: bool,
     pub control_down: bool,
     pub cursor_down: bool,
+    pub keyboard_span: bool,
     pub selected_tile: Option<(usize, usize)>,
     pub hovered_tile: Option<(usize, usize)>,
+    pub selected_zone: Option<usize>,
+    pub hovered_zone: Option<usize>,
     pub active_window: Option<Window>,
     pub grid_window: Option<Window>,
-    pub previous_resize: Option<(Window, Rect)>,
     pub quick_resize: bool,
+    profile_quick_resize: bool, // active profile closes the grid after every placement
+    profile_keys: Vec<(i32, String)>, // virtual key code & the profile it switches to
+    windows: Vec<Window>,       // windows listed below the tiles
     grid_margins: u8,
-    zone_margins: u8,
-    border_margins: u8,
+    spacing: Spacing, // margins & padding of the zones on the active monitor
+    pub theme: Theme,
+    dpi: u32,
     tiles: Vec<Vec<Tile>>, // tiles[row][column]
+    row_weights: Vec<u32>,
+    column_weights: Vec<u32>,
+    zones: Vec<ZoneTile>, // custom layout, replaces tiles when not empty
     active_config: GridConfigKey,
-    configs: GridConfigs,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
-pub struct GridConfig {
-    rows: usize,
-    columns: usize,
+/// Key of the active profile's grid on `monitor`
+pub fn grid_key(monitor: &MonitorDescriptor) -> GridConfigKey {
+    GridConfigKey::new(monitor, &ACTIVE_PROFILE.lock().unwrap())
 }
 
-impl Default for GridConfig {
-    fn default() -> Self {
-        GridConfig {
-            rows: 2,
-            columns: 2,
-        }
-    }
+/// Key of the active profile's grid on the monitor the cursor is on
+pub fn active_grid_key() -> GridConfigKey {
+    grid_key(&get_active_monitor().descriptor())
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
-pub struct GridConfigKey {
-    monitor: String,
-    profile: String,
-}
+/// Grid remembered for `key`, including changes not written to disk yet
+fn cached_grid(key: &GridConfigKey) -> GridConfig {
+    let monitors = get_monitors()
+        .into_iter()
+        .map(|monitor| monitor.descriptor())
+        .collect::<Vec<_>>();
 
-impl Default for GridConfigKey {
-    fn default() -> Self {
-        let monitor = unsafe { get_active_monitor_name() };
-        let profile = ACTIVE_PROFILE.lock().unwrap().clone();
-
-        GridConfigKey { monitor, profile }
-    }
-}
+    GRID_CACHE.migrate_keys(&monitors);
 
-pub type GridConfigs = HashMap<GridConfigKey, GridConfig>;
-pub trait GridCache {
-    fn load() -> GridConfigs;
-    fn save(&self);
+    GRID_CACHE.get(key).unwrap_or_default()
 }
 
-impl GridCache for GridConfigs {
-    fn load() -> GridConfigs {
-        if let Some(mut config_path) = dirs::config_dir() {
-            config_path.push("grout");
-            config_path.push("cache");
-
-            if !config_path.exists() {
-                let _ = fs::create_dir_all(&config_path);
-            }
+impl From<&Config> for Grid {
+    fn from(config: &Config) -> Self {
+        let profile = ACTIVE_PROFILE.lock().unwrap().clone();
 
-            config_path.push("grid.ron");
+        let mut grid = Grid {
+            spacing: config.spacing(&profile, &get_active_monitor().descriptor()),
+            theme: config.theme(&profile),
+            profile_quick_resize: config.quick_resize(&profile),
+            profile_keys: config
+                .profile_keys()
+                .into_iter()
+                .filter_map(|(key, profile)| {
+                    let vk_code = key_vkcode(key.parse().ok()?)?;
+
+                    Some((vk_code as i32, profile))
+                })
+                .collect(),
+            ..Default::default()
+        };
 
-            if let Ok(file) = fs::File::open(config_path) {
-                if let Ok(config) = ron::de::from_reader(file) {
-                    return config;
-                }
-            }
+        if let Some(zones) = config.layout(&profile) {
+            grid.zones = zones
+                .iter()
+                .cloned()
+                .map(|zone| ZoneTile {
+                    zone,
+                    tile: Tile::default(),
+                })
+                .collect();
         }
 
-        let mut config = HashMap::new();
-        config.insert(GridConfigKey::default(), GridConfig::default());
-        config
-    }
-
-    fn save(&self) {
-        if let Some(mut config_path) = dirs::config_dir() {
-            config_path.push("grout");
-            config_path.push("cache");
-            config_path.push("grid.ron");
-
-            if let Ok(serialized) = ron::ser::to_string(&self) {
-                let _ = fs::write(config_path, serialized);
-            }
+        if let Some(weights) = normalize_weights(config.row_weights.as_deref(), grid.rows()) {
+            grid.row_weights = weights;
         }
-    }
-}
 
-impl From<&Config> for Grid {
-    fn from(config: &Config) -> Self {
-        Grid {
-            zone_margins: config.margins,
-            border_margins: config.window_padding,
-            ..Default::default()
+        if let Some(weights) = normalize_weights(config.column_weights.as_deref(), grid.columns()) {
+            grid.column_weights = weights;
         }
+
+        grid
     }
 }
 
 impl Default for Grid {
     fn default() -> Self {
-        let configs = GridConfigs::load();
-        let active_config = GridConfigKey::default();
+        let active_config = active_grid_key();
 
-        let default_config = configs.get(&active_config).cloned().unwrap_or_default();
+        let default_config = cached_grid(&active_config);
 
         let rows = default_config.rows;
         let columns = default_config.columns;
 
+        let row_weights = normalize_weights(Some(&default_config.row_weights), rows)
+            .unwrap_or_else(|| vec![1; rows]);
+        let column_weights = normalize_weights(Some(&default_config.column_weights), columns)
+            .unwrap_or_else(|| vec![1; columns]);
+
         Grid {
             shift_down: false,
             control_down: false,
             cursor_down: false,
+            keyboard_span: false,
             selected_tile: None,
             hovered_tile: None,
+            selected_zone: None,
+            hovered_zone: None,
             active_window: None,
             grid_window: None,
-            previous_resize: None,
             quick_resize: false,
+            profile_quick_resize: false,
+            profile_keys: vec![],
+            windows: vec![],
             grid_margins: 3,
-            zone_margins: 10,
-            border_margins: 10,
+            spacing: Spacing::default(),
+            theme: Theme::default(),
+            dpi: unsafe { get_active_monitor_dpi() },
             tiles: vec![vec![Tile::default(); columns]; rows],
+            row_weights,
+            column_weights,
+            zones: vec![],
             active_config,
-            configs,
         }
     }
 }
@@ -152,8 +174,11 @@ impl Grid {
         self.shift_down = false;
         self.control_down = false;
         self.cursor_down = false;
+        self.keyboard_span = false;
         self.selected_tile = None;
         self.hovered_tile = None;
+        self.selected_zone = None;
+        self.hovered_zone = None;
         self.grid_window = None;
         self.quick_resize = false;
 
@@ -163,62 +188,133 @@ impl Grid {
                 tile.hovered = false;
             })
         });
+
+        self.zones.iter_mut().for_each(|zone| {
+            zone.tile.selected = false;
+            zone.tile.hovered = false;
+        });
+    }
+
+    /// Grid is showing a custom zone layout instead of rows & columns
+    pub fn has_layout(&self) -> bool {
+        !self.zones.is_empty()
     }
 
     fn save_config(&mut self) {
-        let rows = self.rows();
-        let columns = self.columns();
+        let grid_config = GridConfig {
+            rows: self.rows(),
+            columns: self.columns(),
+            row_weights: self.row_weights.clone(),
+            column_weights: self.column_weights.clone(),
+        };
 
-        if let Some(grid_config) = self.configs.get_mut(&self.active_config) {
-            grid_config.rows = rows;
-            grid_config.columns = columns;
-        } else {
-            self.configs
-                .insert(self.active_config.clone(), GridConfig { rows, columns });
-        }
+        GRID_CACHE.insert(self.active_config.clone(), grid_config);
+    }
 
-        self.configs.save();
+    /// Config value in logical units, scaled to physical pixels on the active monitor
+    fn scaled(&self, value: u32) -> i32 {
+        dpi::scale(value as i32, self.dpi)
     }
 
+    /// Size of the grid window, fitting the tiles and the window list below them
     pub fn dimensions(&self) -> (u32, u32) {
-        let width = self.columns() as u32 * TILE_WIDTH
-            + (self.columns() as u32 + 1) * self.grid_margins as u32;
+        let (width, height) = self.tiles_dimensions();
+
+        if self.windows.is_empty() {
+            return (width, height);
+        }
+
+        let grid_margins = self.scaled(self.grid_margins as u32) as u32;
+        let list_height = self.windows.len() as u32 * self.scaled(LIST_ROW_HEIGHT) as u32;
 
-        let height =
-            self.rows() as u32 * TILE_HEIGHT + (self.rows() as u32 + 1) * self.grid_margins as u32;
+        (
+            width.max(self.scaled(LIST_WIDTH) as u32 + 2 * grid_margins),
+            height + list_height + grid_margins,
+        )
+    }
+
+    fn tiles_dimensions(&self) -> (u32, u32) {
+        let grid_margins = self.scaled(self.grid_margins as u32) as u32;
+
+        if self.has_layout() {
+            let work_area = unsafe { get_work_area() };
+
+            let width = self.scaled(LAYOUT_WIDTH) as u32;
+            let height = width * work_area.height.max(1) as u32 / work_area.width.max(1) as u32;
+
+            return (width + 2 * grid_margins, height + 2 * grid_margins);
+        }
+
+        let width = self.columns() as u32 * self.scaled(TILE_WIDTH) as u32
+            + (self.columns() as u32 + 1) * grid_margins;
+
+        let height = self.rows() as u32 * self.scaled(TILE_HEIGHT) as u32
+            + (self.rows() as u32 + 1) * grid_margins;
 
         (width, height)
     }
 
+    /// Zone math for the work area of the active monitor
+    fn zone_geometry(&self) -> GridGeometry<'_> {
+        let work_area = unsafe { get_work_area() };
+
+        GridGeometry {
+            area: work_area,
+            padding: self.spacing.padding(work_area, self.dpi),
+            margin: self.spacing.margin(work_area, self.dpi),
+            row_weights: &self.row_weights,
+            column_weights: &self.column_weights,
+        }
+    }
+
     fn zone_area(&self, row: usize, column: usize) -> Rect {
+        self.zone_geometry().zone(row, column)
+    }
+
+    /// Area covering every zone between two tiles, inclusive
+    fn span_area(&self, from_tile: (usize, usize), to_tile: (usize, usize)) -> Rect {
+        self.zone_geometry().span(from_tile, to_tile)
+    }
+
+    /// Area of a custom layout zone within the work area
+    fn layout_zone_area(&self, idx: usize) -> Rect {
         let work_area = unsafe { get_work_area() };
 
-        let zone_width = (work_area.width
-            - self.border_margins as i32 * 2
-            - (self.columns() - 1) as i32 * self.zone_margins as i32)
-            / self.columns() as i32;
-        let zone_height = (work_area.height
-            - self.border_margins as i32 * 2
-            - (self.rows() - 1) as i32 * self.zone_margins as i32)
-            / self.rows() as i32;
-
-        let x = column as i32 * zone_width
-            + self.border_margins as i32
-            + column as i32 * self.zone_margins as i32
-            + work_area.x;
-        let y = row as i32 * zone_height
-            + self.border_margins as i32
-            + row as i32 * self.zone_margins as i32
-            + work_area.y;
+        fraction_area(
+            self.spacing.padded_area(work_area, self.dpi),
+            &self.zones[idx].zone,
+            self.spacing.margin(work_area, self.dpi),
+        )
+    }
+
+    /// Area of the tiles within the grid window, centered above the window list
+    fn tiles_area(&self) -> Rect {
+        let (width, _) = self.dimensions();
+        let (tiles_width, tiles_height) = self.tiles_dimensions();
 
         Rect {
-            x,
-            y,
-            width: zone_width,
-            height: zone_height,
+            x: (width - tiles_width) as i32 / 2,
+            y: 0,
+            width: tiles_width as i32,
+            height: tiles_height as i32,
         }
     }
 
+    /// Area of a custom layout zone within the grid window
+    fn layout_tile_area(&self, idx: usize) -> Rect {
+        let tiles_area = self.tiles_area();
+        let grid_margins = self.scaled(self.grid_margins as u32);
+
+        let area = Rect {
+            x: tiles_area.x + grid_margins,
+            y: tiles_area.y + grid_margins,
+            width: tiles_area.width - grid_margins * 2,
+            height: tiles_area.height - grid_margins * 2,
+        };
+
+        fraction_area(area, &self.zones[idx].zone, grid_margins)
+    }
+
     fn rows(&self) -> usize {
         self.tiles.len()
     }
@@ -228,44 +324,63 @@ impl Grid {
     }
 
     pub fn add_row(&mut self) {
+        if self.has_layout() {
+            return;
+        }
+
         self.tiles.push(vec![Tile::default(); self.columns()]);
+        self.row_weights.push(1);
         self.save_config();
     }
 
     pub fn add_column(&mut self) {
+        if self.has_layout() {
+            return;
+        }
+
         for row in self.tiles.iter_mut() {
             row.push(Tile::default());
         }
+        self.column_weights.push(1);
         self.save_config();
     }
 
     pub fn remove_row(&mut self) {
+        if self.has_layout() {
+            return;
+        }
+
         if self.rows() > 1 {
             self.tiles.pop();
+            self.row_weights.pop();
         }
         self.save_config();
     }
 
     pub fn remove_column(&mut self) {
+        if self.has_layout() {
+            return;
+        }
+
         if self.columns() > 1 {
             for row in self.tiles.iter_mut() {
                 row.pop();
             }
+            self.column_weights.pop();
         }
         self.save_config();
     }
 
     fn tile_area(&self, row: usize, column: usize) -> Rect {
-        let x = column as i32 * TILE_WIDTH as i32 + (column as i32 + 1) * self.grid_margins as i32;
-
-        let y = row as i32 * TILE_HEIGHT as i32 + (row as i32 + 1) * self.grid_margins as i32;
+        let geometry = GridGeometry {
+            area: self.tiles_area(),
+            padding: Insets::uniform(self.scaled(self.grid_margins as u32)),
+            margin: self.scaled(self.grid_margins as u32),
+            row_weights: &self.row_weights,
+            column_weights: &self.column_weights,
+        };
 
-        Rect {
-            x,
-            y,
-            width: TILE_WIDTH as i32,
-            height: TILE_HEIGHT as i32,
-        }
+        geometry.zone(row, column)
     }
 
     pub fn reposition(&mut self) {
@@ -282,8 +397,38 @@ impl Grid {
         self.grid_window.as_mut().unwrap().set_pos(rect, None);
     }
 
+    /// Profile the key switches to, if any
+    pub fn profile_for_key(&self, vk_code: i32) -> Option<String> {
+        self.profile_keys
+            .iter()
+            .find(|(key, _)| *key == vk_code)
+            .map(|(_, profile)| profile.clone())
+    }
+
+    /// Whether placing a window closes the grid, either for this session or for every
+    /// session of the active profile
+    pub fn closes_after_placement(&self) -> bool {
+        self.quick_resize || self.profile_quick_resize
+    }
+
+    /// Repaints the grid window from outside its own thread, such as after another
+    /// window got focus
+    pub fn redraw(&self) {
+        if let Some(grid_window) = self.grid_window {
+            unsafe {
+                InvalidateRect(grid_window.0, ptr::null(), 0);
+            }
+        }
+    }
+
     /// Returns true if a change in highlighting occured
     pub unsafe fn highlight_tiles(&mut self, point: (i32, i32)) -> Option<Rect> {
+        self.keyboard_span = false;
+
+        if self.has_layout() {
+            return self.highlight_zones(point);
+        }
+
         let original_tiles = self.tiles.clone();
         let mut hovered_rect = None;
 
@@ -313,75 +458,59 @@ impl Grid {
         }
     }
 
+    unsafe fn highlight_zones(&mut self, point: (i32, i32)) -> Option<Rect> {
+        let original_zones = self.zones.clone();
+        let mut hovered_rect = None;
+
+        for idx in 0..self.zones.len() {
+            if self.layout_tile_area(idx).contains_point(point) {
+                self.zones[idx].tile.hovered = true;
+
+                self.hovered_zone = Some(idx);
+                hovered_rect = Some(self.layout_zone_area(idx));
+            } else {
+                self.zones[idx].tile.hovered = false;
+            }
+        }
+
+        if original_zones == self.zones {
+            None
+        } else {
+            hovered_rect
+        }
+    }
+
     unsafe fn shift_hover_and_calc_rect(&mut self, highlight: bool) -> Option<Rect> {
-        if self.shift_down || self.cursor_down {
+        let (from_tile, to_tile) = self.shift_span()?;
+
+        let hovered_rect = self.span_area(from_tile, to_tile);
+
+        if highlight {
+            for row in from_tile.0..=to_tile.0 {
+                for column in from_tile.1..=to_tile.1 {
+                    self.tiles[row][column].hovered = true;
+                }
+            }
+        }
+
+        Some(hovered_rect)
+    }
+
+    /// Span between the selected & hovered tile while extending the selection
+    fn shift_span(&self) -> Option<Span> {
+        if self.shift_down || self.cursor_down || self.keyboard_span {
             if let Some(selected_tile) = self.selected_tile {
                 if let Some(hovered_tile) = self.hovered_tile {
-                    let selected_zone = self.zone_area(selected_tile.0, selected_tile.1);
-                    let hovered_zone = self.zone_area(hovered_tile.0, hovered_tile.1);
-
-                    let from_tile;
-                    let to_tile;
-
-                    let hovered_rect = if hovered_zone.x < selected_zone.x
-                        && hovered_zone.y > selected_zone.y
-                    {
-                        from_tile = (selected_tile.0, hovered_tile.1);
-                        to_tile = (hovered_tile.0, selected_tile.1);
-
-                        let from_zone = self.zone_area(from_tile.0, from_tile.1);
-                        let to_zone = self.zone_area(to_tile.0, to_tile.1);
-
-                        Rect {
-                            x: from_zone.x,
-                            y: from_zone.y,
-                            width: (to_zone.x + to_zone.width) - from_zone.x,
-                            height: (to_zone.y + to_zone.height) - from_zone.y,
-                        }
-                    } else if hovered_zone.y < selected_zone.y && hovered_zone.x > selected_zone.x {
-                        from_tile = (hovered_tile.0, selected_tile.1);
-                        to_tile = (selected_tile.0, hovered_tile.1);
-
-                        let from_zone = self.zone_area(from_tile.0, from_tile.1);
-                        let to_zone = self.zone_area(to_tile.0, to_tile.1);
-
-                        Rect {
-                            x: from_zone.x,
-                            y: from_zone.y,
-                            width: (to_zone.x + to_zone.width) - from_zone.x,
-                            height: (to_zone.y + to_zone.height) - from_zone.y,
-                        }
-                    } else if hovered_zone.x > selected_zone.x || hovered_zone.y > selected_zone.y {
-                        from_tile = selected_tile;
-                        to_tile = hovered_tile;
-
-                        Rect {
-                            x: selected_zone.x,
-                            y: selected_zone.y,
-                            width: (hovered_zone.x + hovered_zone.width) - selected_zone.x,
-                            height: (hovered_zone.y + hovered_zone.height) - selected_zone.y,
-                        }
-                    } else {
-                        from_tile = hovered_tile;
-                        to_tile = selected_tile;
-
-                        Rect {
-                            x: hovered_zone.x,
-                            y: hovered_zone.y,
-                            width: (selected_zone.x + selected_zone.width) - hovered_zone.x,
-                            height: (selected_zone.y + selected_zone.height) - hovered_zone.y,
-                        }
-                    };
-
-                    if highlight {
-                        for row in from_tile.0..=to_tile.0 {
-                            for column in from_tile.1..=to_tile.1 {
-                                self.tiles[row][column].hovered = true;
-                            }
-                        }
-                    }
-
-                    return Some(hovered_rect);
+                    let from_tile = (
+                        selected_tile.0.min(hovered_tile.0),
+                        selected_tile.1.min(hovered_tile.1),
+                    );
+                    let to_tile = (
+                        selected_tile.0.max(hovered_tile.0),
+                        selected_tile.1.max(hovered_tile.1),
+                    );
+
+                    return Some((from_tile, to_tile));
                 }
             }
         }
@@ -389,11 +518,95 @@ impl Grid {
         None
     }
 
+    /// Moves the keyboard cursor one tile in `direction`. When `extend` is true, the
+    /// selected tile is kept as an anchor and the span between it and the cursor is
+    /// highlighted. Returns the zone area to preview.
+    pub unsafe fn move_cursor(&mut self, direction: Direction, extend: bool) -> Option<Rect> {
+        if self.has_layout() {
+            return self.move_zone_cursor(direction);
+        }
+
+        let next_tile = match self.hovered_tile.or(self.selected_tile) {
+            Some((row, column)) => match direction {
+                Direction::Left => (row, column.saturating_sub(1)),
+                Direction::Right => (row, (column + 1).min(self.columns() - 1)),
+                Direction::Up => (row.saturating_sub(1), column),
+                Direction::Down => ((row + 1).min(self.rows() - 1), column),
+            },
+            None => (0, 0),
+        };
+
+        if !extend || self.selected_tile.is_none() {
+            self.unselect_all_tiles();
+            self.tiles[next_tile.0][next_tile.1].selected = true;
+            self.selected_tile = Some(next_tile);
+        }
+
+        self.keyboard_span = extend;
+        self.hovered_tile = Some(next_tile);
+
+        self.unhighlight_all_tiles();
+        self.tiles[next_tile.0][next_tile.1].hovered = true;
+
+        self.shift_hover_and_calc_rect(true)
+            .or_else(|| Some(self.zone_area(next_tile.0, next_tile.1)))
+    }
+
+    fn move_zone_cursor(&mut self, direction: Direction) -> Option<Rect> {
+        let next_zone = match self.hovered_zone.or(self.selected_zone) {
+            Some(idx) => self.adjacent_zone(idx, direction).unwrap_or(idx),
+            None => 0,
+        };
+
+        self.unselect_all_tiles();
+        self.unhighlight_all_tiles();
+
+        self.zones[next_zone].tile.selected = true;
+        self.zones[next_zone].tile.hovered = true;
+        self.selected_zone = Some(next_zone);
+        self.hovered_zone = Some(next_zone);
+
+        Some(self.layout_zone_area(next_zone))
+    }
+
+    /// Closest zone whose center lies in `direction` from the center of zone `idx`
+    fn adjacent_zone(&self, idx: usize, direction: Direction) -> Option<usize> {
+        let center = |zone: &Zone| (zone.x + zone.width / 2.0, zone.y + zone.height / 2.0);
+        let from = center(&self.zones[idx].zone);
+
+        self.zones
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != idx)
+            .filter_map(|(other, zone)| {
+                let to = center(&zone.zone);
+                let (along, across) = match direction {
+                    Direction::Left => (from.0 - to.0, to.1 - from.1),
+                    Direction::Right => (to.0 - from.0, to.1 - from.1),
+                    Direction::Up => (from.1 - to.1, to.0 - from.0),
+                    Direction::Down => (to.1 - from.1, to.0 - from.0),
+                };
+
+                if along > 0.0 {
+                    // Favour zones in line with the current one over diagonal ones
+                    Some((other, along + across.abs() * 2.0))
+                } else {
+                    None
+                }
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(other, _)| other)
+    }
+
     pub unsafe fn select_tile(&mut self, point: (i32, i32)) -> bool {
         if self.cursor_down || self.shift_down {
             return false;
         }
 
+        if self.has_layout() {
+            return self.select_zone(point);
+        }
+
         let previously_selected = self.selected_tile;
 
         for row in 0..self.rows() {
@@ -413,19 +626,103 @@ impl Grid {
         self.selected_tile != previously_selected
     }
 
-    pub fn get_max_area(&self) -> Rect {
-        let from_zone = self.zone_area(0, 0);
-        let to_zone = self.zone_area(self.rows() - 1, self.columns() - 1);
+    fn select_zone(&mut self, point: (i32, i32)) -> bool {
+        let previously_selected = self.selected_zone;
+
+        for idx in 0..self.zones.len() {
+            if self.layout_tile_area(idx).contains_point(point) {
+                self.zones[idx].tile.selected = true;
+
+                self.selected_zone = Some(idx);
+            } else {
+                self.zones[idx].tile.selected = false;
+            }
+        }
+
+        self.selected_zone != previously_selected
+    }
+
+    /// Updates the window list, keeping listed windows in place so rows don't jump
+    /// around while placing windows. Returns true if the list changed.
+    pub fn refresh_window_list(&mut self) -> bool {
+        let grid_window = self.grid_window;
+        let visible = get_visible_windows()
+            .into_iter()
+            .filter(|window| Some(*window) != grid_window)
+            .collect::<Vec<_>>();
+
+        let mut windows = self
+            .windows
+            .iter()
+            .copied()
+            .filter(|window| visible.contains(window))
+            .collect::<Vec<_>>();
+
+        for window in visible {
+            if windows.len() < MAX_LIST_ROWS && !windows.contains(&window) {
+                windows.push(window);
+            }
+        }
+
+        let changed = windows != self.windows;
+        self.windows = windows;
+
+        changed
+    }
+
+    fn list_row_area(&self, idx: usize) -> Rect {
+        let (width, _) = self.dimensions();
+        let (_, tiles_height) = self.tiles_dimensions();
+        let grid_margins = self.scaled(self.grid_margins as u32);
+        let row_height = self.scaled(LIST_ROW_HEIGHT);
 
         Rect {
-            x: from_zone.x,
-            y: from_zone.y,
-            width: (to_zone.x + to_zone.width) - from_zone.x,
-            height: (to_zone.y + to_zone.height) - from_zone.y,
+            x: grid_margins,
+            y: tiles_height as i32 + idx as i32 * row_height,
+            width: width as i32 - grid_margins * 2,
+            height: row_height - grid_margins,
         }
     }
 
+    /// Listed window under `point`, in grid window coordinates
+    pub fn listed_window_at(&self, point: (i32, i32)) -> Option<Window> {
+        (0..self.windows.len())
+            .find(|idx| self.list_row_area(*idx).contains_point(point))
+            .map(|idx| self.windows[idx])
+    }
+
+    /// Makes the next listed window, or previous if not `forward`, the one to place
+    /// next. Returns true if the grid needs to be repainted.
+    pub fn cycle_active_window(&mut self, forward: bool) -> bool {
+        let count = self.windows.len();
+
+        if count == 0 {
+            return false;
+        }
+
+        let next = match self
+            .active_window
+            .and_then(|active| self.windows.iter().position(|window| *window == active))
+        {
+            Some(idx) if forward => (idx + 1) % count,
+            Some(idx) => (idx + count - 1) % count,
+            None => 0,
+        };
+
+        self.active_window = Some(self.windows[next]);
+
+        true
+    }
+
+    pub fn get_max_area(&self) -> Rect {
+        self.zone_geometry().max_area()
+    }
+
     pub unsafe fn selected_area(&mut self) -> Option<Rect> {
+        if self.has_layout() {
+            return self.selected_zone.map(|idx| self.layout_zone_area(idx));
+        }
+
         if let Some(shift_rect) = self.shift_hover_and_calc_rect(false) {
             return Some(shift_rect);
         }
@@ -437,16 +734,49 @@ impl Grid {
         }
     }
 
+    /// Tiles `selected_area` spans, if a zone of the grid rather than of a custom
+    /// layout is selected
+    pub fn selected_span(&self) -> Option<Span> {
+        if self.has_layout() {
+            return None;
+        }
+
+        self.shift_span()
+            .or_else(|| self.selected_tile.map(|tile| (tile, tile)))
+    }
+
+    /// Moves the stacks on the active monitor along with this grid, after its rows or
+    /// columns changed. Returns each window that needs moving, along with its new
+    /// zone area.
+    pub fn follow_grid(&self, stacks: &mut Stacks<Window>) -> Vec<(Window, Rect)> {
+        if self.has_layout() {
+            return vec![];
+        }
+
+        stacks.follow_grid(
+            &get_active_monitor().descriptor().id(),
+            self.rows(),
+            self.columns(),
+            |(from_tile, to_tile)| self.span_area(from_tile, to_tile),
+        )
+    }
+
     pub fn unhighlight_all_tiles(&mut self) {
         self.tiles
             .iter_mut()
             .for_each(|row| row.iter_mut().for_each(|tile| tile.hovered = false));
+        self.zones
+            .iter_mut()
+            .for_each(|zone| zone.tile.hovered = false);
     }
 
     pub fn unselect_all_tiles(&mut self) {
         self.tiles
             .iter_mut()
             .for_each(|row| row.iter_mut().for_each(|tile| tile.selected = false));
+        self.zones
+            .iter_mut()
+            .for_each(|zone| zone.tile.selected = false);
     }
 
     pub unsafe fn draw(&self, window: Window) {
@@ -455,14 +785,81 @@ impl Grid {
 
         let hdc = BeginPaint(window.0, &mut paint);
 
-        for row in 0..self.rows() {
-            for column in 0..self.columns() {
-                self.tiles[row][column].draw(hdc, self.tile_area(row, column));
+        let mut client_area = mem::zeroed();
+        GetClientRect(window.0, &mut client_area);
+
+        let background = CreateSolidBrush(self.theme.background.colorref());
+        FillRect(hdc, &client_area, background);
+        DeleteObject(background as *mut _);
+
+        for (idx, zone) in self.zones.iter().enumerate() {
+            zone.tile.draw(hdc, self.layout_tile_area(idx), &self.theme);
+        }
+
+        if !self.has_layout() {
+            for row in 0..self.rows() {
+                for column in 0..self.columns() {
+                    self.tiles[row][column].draw(hdc, self.tile_area(row, column), &self.theme);
+                }
             }
         }
 
+        self.draw_window_list(hdc);
+
         EndPaint(window.0, &paint);
     }
+
+    /// Lists the windows to place, highlighting the active one and dimming the ones
+    /// already placed while the grid was open
+    unsafe fn draw_window_list(&self, hdc: HDC) {
+        let placed = HISTORY.lock().unwrap().group_windows();
+
+        SetBkMode(hdc, TRANSPARENT as i32);
+
+        for (idx, window) in self.windows.iter().enumerate() {
+            let area = self.list_row_area(idx);
+
+            if self.active_window == Some(*window) {
+                let brush = CreateSolidBrush(self.theme.tile_selected.colorref());
+                FillRect(hdc, &area.into(), brush);
+                DeleteObject(brush as *mut _);
+            }
+
+            if placed.contains(window) {
+                SetTextColor(hdc, RGB(140, 140, 140));
+            } else {
+                SetTextColor(hdc, RGB(230, 230, 230));
+            }
+
+            let title = str_to_wide!(window.title());
+            let mut text_area = Rect {
+                x: area.x + self.scaled(6),
+                width: area.width - self.scaled(12),
+                ..area
+            }
+            .into();
+
+            DrawTextW(
+                hdc,
+                title.as_ptr(),
+                -1,
+                &mut text_area,
+                DT_LEFT | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS | DT_NOPREFIX,
+            );
+        }
+    }
+}
+
+/// Row & column weights of the grid last used on `monitor`, without opening the
+/// grid window. The number of weights gives the number of rows & columns.
+pub fn monitor_grid_weights(monitor: &MonitorDescriptor, config: &Config) -> (Vec<u32>, Vec<u32>) {
+    cached_grid(&grid_key(monitor)).weights(config)
+}
+
+#[derive(Clone, PartialEq)]
+struct ZoneTile {
+    zone: Zone,
+    tile: Tile,
 }
 
 #[derive(Default, Clone, Copy, PartialEq)]
@@ -472,9 +869,9 @@ struct Tile {
 }
 
 impl Tile {
-    unsafe fn draw(self, hdc: HDC, area: Rect) {
-        let fill_brush = self.fill_brush();
-        let frame_brush = CreateSolidBrush(RGB(0, 0, 0));
+    unsafe fn draw(self, hdc: HDC, area: Rect, theme: &Theme) {
+        let fill_brush = self.fill_brush(theme);
+        let frame_brush = CreateSolidBrush(theme.tile_border.colorref());
 
         FillRect(hdc, &area.into(), fill_brush);
         FrameRect(hdc, &area.into(), frame_brush);
@@ -483,19 +880,15 @@ impl Tile {
         DeleteObject(frame_brush as *mut _);
     }
 
-    unsafe fn fill_brush(self) -> HBRUSH {
+    unsafe fn fill_brush(self, theme: &Theme) -> HBRUSH {
         let color = if self.selected {
-            RGB(0, 77, 128)
+            theme.tile_selected
         } else if self.hovered {
-            RGB(0, 100, 148)
+            theme.tile_hovered
         } else {
-            RGB(
-                (255.0 * (70.0 / 100.0)) as u8,
-                (255.0 * (70.0 / 100.0)) as u8,
-                (255.0 * (70.0 / 100.0)) as u8,
-            )
+            theme.tile
         };
 
-        CreateSolidBrush(color)
+        CreateSolidBrush(color.colorref())
     }
 }
@@ -0,0 +1,225 @@
+//! Rows, columns & weights of the grid used on a monitor, and the area of its tiles.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::geometry::GridGeometry;
+use crate::monitor_id::{is_legacy_id, migrate_id, MonitorDescriptor};
+use crate::Rect;
+
+/// Rows & columns of the grid used on a monitor, as remembered in the grid cache
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GridConfig {
+    pub rows: usize,
+    pub columns: usize,
+    #[serde(default)]
+    pub row_weights: Vec<u32>,
+    #[serde(default)]
+    pub column_weights: Vec<u32>,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig {
+            rows: 2,
+            columns: 2,
+            row_weights: vec![],
+            column_weights: vec![],
+        }
+    }
+}
+
+impl GridConfig {
+    /// Row & column weights, taken from `config` if it has one for every row / column,
+    /// otherwise the remembered ones. Falls back to equally sized tiles.
+    pub fn weights(&self, config: &Config) -> (Vec<u32>, Vec<u32>) {
+        let row_weights = normalize_weights(config.row_weights.as_deref(), self.rows)
+            .or_else(|| normalize_weights(Some(&self.row_weights), self.rows))
+            .unwrap_or_else(|| vec![1; self.rows]);
+        let column_weights = normalize_weights(config.column_weights.as_deref(), self.columns)
+            .or_else(|| normalize_weights(Some(&self.column_weights), self.columns))
+            .unwrap_or_else(|| vec![1; self.columns]);
+
+        (row_weights, column_weights)
+    }
+
+    /// Area of every tile, as `zones[row][column]`, when the grid is laid out over the
+    /// `work_area` of `monitor`. Margins & padding of `profile` are scaled to `dpi`.
+    pub fn zones(
+        &self,
+        config: &Config,
+        profile: &str,
+        monitor: &MonitorDescriptor,
+        work_area: Rect,
+        dpi: u32,
+    ) -> Vec<Vec<Rect>> {
+        let (row_weights, column_weights) = self.weights(config);
+        let spacing = config.spacing(profile, monitor);
+
+        let geometry = GridGeometry {
+            area: work_area,
+            padding: spacing.padding(work_area, dpi),
+            margin: spacing.margin(work_area, dpi),
+            row_weights: &row_weights,
+            column_weights: &column_weights,
+        };
+
+        (0..row_weights.len())
+            .map(|row| {
+                (0..column_weights.len())
+                    .map(|column| geometry.zone(row, column))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Identifies the grid of a profile on a monitor
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct GridConfigKey {
+    /// Stable monitor id, see `MonitorDescriptor::id`
+    monitor: String,
+    profile: String,
+}
+
+impl GridConfigKey {
+    /// Key of the grid of `profile` on `monitor`
+    pub fn new(monitor: &MonitorDescriptor, profile: &str) -> Self {
+        GridConfigKey {
+            monitor: monitor.id(),
+            profile: profile.to_owned(),
+        }
+    }
+}
+
+/// Grids remembered for each monitor & profile
+pub type GridConfigs = HashMap<GridConfigKey, GridConfig>;
+
+/// Rewrites keys still using device names to the stable id of the connected monitor
+/// going by that name. Keys of disconnected monitors are left for when they're back,
+/// and existing entries under the stable id win. Returns true if any key changed.
+pub fn migrate_keys(configs: &mut GridConfigs, monitors: &[MonitorDescriptor]) -> bool {
+    let legacy_keys = configs
+        .keys()
+        .filter(|key| is_legacy_id(&key.monitor))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut migrated = false;
+
+    for legacy_key in legacy_keys {
+        if let Some(monitor) = migrate_id(&legacy_key.monitor, monitors) {
+            let key = GridConfigKey {
+                monitor,
+                profile: legacy_key.profile.clone(),
+            };
+
+            if let Some(grid_config) = configs.remove(&legacy_key) {
+                configs.entry(key).or_insert(grid_config);
+                migrated = true;
+            }
+        }
+    }
+
+    migrated
+}
+
+/// Drops the NUL characters device names were padded with in version 1 of the cache
+pub(crate) fn strip_nul_padding(configs: &mut GridConfigs) {
+    *configs = configs
+        .drain()
+        .map(|(key, grid_config)| {
+            let key = GridConfigKey {
+                monitor: key.monitor.trim_end_matches('\0').to_owned(),
+                ..key
+            };
+
+            (key, grid_config)
+        })
+        .collect();
+}
+
+/// Weights are only used if there is one for each row / column. Zero weights are
+/// bumped to 1 so every tile keeps a usable size.
+pub fn normalize_weights(weights: Option<&[u32]>, count: usize) -> Option<Vec<u32>> {
+    weights
+        .filter(|weights| weights.len() == count)
+        .map(|weights| weights.iter().map(|weight| (*weight).max(1)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(device_name: &str, device_path: &str) -> MonitorDescriptor {
+        MonitorDescriptor {
+            device_name: device_name.to_owned(),
+            device_path: device_path.to_owned(),
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    fn key(monitor: &str, profile: &str) -> GridConfigKey {
+        GridConfigKey {
+            monitor: monitor.to_owned(),
+            profile: profile.to_owned(),
+        }
+    }
+
+    fn grid(rows: usize, columns: usize) -> GridConfig {
+        GridConfig {
+            rows,
+            columns,
+            ..GridConfig::default()
+        }
+    }
+
+    #[test]
+    fn migrates_connected_monitors() {
+        let docked = monitor(r"\\.\DISPLAY2", r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352");
+        let laptop = monitor(r"\\.\DISPLAY1", "");
+
+        let mut configs = GridConfigs::new();
+        configs.insert(key(r"\\.\DISPLAY2", "Default"), grid(3, 4));
+        configs.insert(key(r"\\.\DISPLAY2", "Work"), grid(1, 2));
+        configs.insert(key(r"\\.\DISPLAY1", "Default"), grid(2, 2));
+        configs.insert(key(r"\\.\DISPLAY5", "Default"), grid(5, 5));
+
+        assert!(migrate_keys(
+            &mut configs,
+            &[laptop.clone(), docked.clone()]
+        ));
+
+        assert_eq!(configs[&GridConfigKey::new(&docked, "Default")], grid(3, 4));
+        assert_eq!(configs[&GridConfigKey::new(&docked, "Work")], grid(1, 2));
+        assert_eq!(configs[&GridConfigKey::new(&laptop, "Default")], grid(2, 2));
+        assert_eq!(configs[&key(r"\\.\DISPLAY5", "Default")], grid(5, 5));
+        assert_eq!(configs.len(), 4);
+
+        assert!(!migrate_keys(&mut configs, &[laptop, docked]));
+    }
+
+    #[test]
+    fn existing_ids_win() {
+        let docked = monitor(r"\\.\DISPLAY2", r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352");
+
+        let mut configs = GridConfigs::new();
+        configs.insert(key(r"\\.\DISPLAY2", "Default"), grid(3, 4));
+        configs.insert(GridConfigKey::new(&docked, "Default"), grid(1, 1));
+
+        assert!(migrate_keys(&mut configs, std::slice::from_ref(&docked)));
+
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[&GridConfigKey::new(&docked, "Default")], grid(1, 1));
+    }
+
+    #[test]
+    fn normalizes_weights() {
+        assert_eq!(normalize_weights(Some(&[2, 0, 1]), 3), Some(vec![2, 1, 1]));
+        assert_eq!(normalize_weights(Some(&[2, 1]), 3), None);
+        assert_eq!(normalize_weights(None, 3), None);
+    }
+}
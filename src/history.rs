@@ -0,0 +1,327 @@
+//! Undo & redo history of window moves.
+
+use std::collections::VecDeque;
+
+use crate::Rect;
+
+/// Number of moves kept per window, and across all windows
+pub const HISTORY_LIMIT: usize = 50;
+
+/// Window moved from one area to another
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move<W> {
+    pub window: W,
+    pub from: Rect,
+    pub to: Rect,
+}
+
+/// Bounded undo / redo history of window moves. Every window keeps its own moves,
+/// while `order` remembers which window moved when, so undo & redo walk back and
+/// forth through the moves of all windows.
+///
+/// Moves recorded between `begin_group` and `end_group`, such as every placement of
+/// a grid session, are undone & redone together.
+pub struct History<W> {
+    windows: Vec<WindowHistory<W>>,
+    order: VecDeque<(W, usize)>,
+    redo_order: Vec<(W, usize)>,
+    limit: usize,
+    next_group: usize,
+    open_group: Option<usize>,
+}
+
+struct WindowHistory<W> {
+    window: W,
+    done: VecDeque<Move<W>>,
+    undone: Vec<Move<W>>,
+}
+
+impl<W: Copy + PartialEq> Default for History<W> {
+    fn default() -> Self {
+        History::with_limit(HISTORY_LIMIT)
+    }
+}
+
+impl<W: Copy + PartialEq> History<W> {
+    /// History keeping up to `limit` moves per window, and across all windows
+    pub fn with_limit(limit: usize) -> Self {
+        History {
+            windows: vec![],
+            order: VecDeque::new(),
+            redo_order: vec![],
+            limit: limit.max(1),
+            next_group: 0,
+            open_group: None,
+        }
+    }
+
+    /// Records a move, dropping every move that could have been redone
+    pub fn record(&mut self, window: W, from: Rect, to: Rect) {
+        if from == to {
+            return;
+        }
+
+        for history in self.windows.iter_mut() {
+            history.undone.clear();
+        }
+        self.redo_order.clear();
+
+        let limit = self.limit;
+        let history = self.window_history(window);
+        history.done.push_back(Move { window, from, to });
+
+        if history.done.len() > limit {
+            history.done.pop_front();
+
+            if let Some(idx) = self.order.iter().position(|(w, _)| *w == window) {
+                self.order.remove(idx);
+            }
+        }
+
+        let group = match self.open_group {
+            Some(group) => group,
+            None => self.new_group(),
+        };
+        self.order.push_back((window, group));
+
+        if self.order.len() > self.limit {
+            if let Some((oldest, _)) = self.order.pop_front() {
+                self.window_history(oldest).done.pop_front();
+            }
+        }
+
+        self.drop_empty();
+    }
+
+    /// Groups every move recorded until `end_group`
+    pub fn begin_group(&mut self) {
+        if self.open_group.is_none() {
+            self.open_group = Some(self.new_group());
+        }
+    }
+
+    /// Stops grouping moves, see `begin_group`
+    pub fn end_group(&mut self) {
+        self.open_group = None;
+    }
+
+    /// Windows moved since `begin_group`, in the order they were first moved
+    pub fn group_windows(&self) -> Vec<W> {
+        let mut windows = vec![];
+
+        if let Some(open_group) = self.open_group {
+            for (window, group) in self.order.iter() {
+                if *group == open_group && !windows.contains(window) {
+                    windows.push(*window);
+                }
+            }
+        }
+
+        windows
+    }
+
+    /// Takes back the most recent move of any window, along with the other moves of
+    /// its group. Windows should be moved back to each returned move's `from`, in
+    /// the order returned.
+    pub fn undo(&mut self) -> Vec<Move<W>> {
+        let mut moves = vec![];
+
+        let group = match self.order.back() {
+            Some((_, group)) => *group,
+            None => return moves,
+        };
+
+        while let Some((window, _)) = self.order.back().filter(|(_, g)| *g == group).copied() {
+            self.order.pop_back();
+
+            let history = self.window_history(window);
+
+            if let Some(last_move) = history.done.pop_back() {
+                history.undone.push(last_move);
+                self.redo_order.push((window, group));
+                moves.push(last_move);
+            }
+        }
+
+        moves
+    }
+
+    /// Repeats the most recently undone move, along with the other moves of its
+    /// group. Windows should be moved to each returned move's `to`, in the order
+    /// returned.
+    pub fn redo(&mut self) -> Vec<Move<W>> {
+        let mut moves = vec![];
+
+        let group = match self.redo_order.last() {
+            Some((_, group)) => *group,
+            None => return moves,
+        };
+
+        while let Some((window, _)) = self.redo_order.last().filter(|(_, g)| *g == group).copied() {
+            self.redo_order.pop();
+
+            let history = self.window_history(window);
+
+            if let Some(undone_move) = history.undone.pop() {
+                history.done.push_back(undone_move);
+                self.order.push_back((window, group));
+                moves.push(undone_move);
+            }
+        }
+
+        moves
+    }
+
+    /// Most recent move of `window` that hasn't been undone
+    pub fn last_move(&self, window: W) -> Option<Move<W>> {
+        self.windows
+            .iter()
+            .find(|history| history.window == window)
+            .and_then(|history| history.done.back().copied())
+    }
+
+    /// Forgets every move of windows that don't satisfy `keep`, such as closed ones
+    pub fn retain(&mut self, keep: impl Fn(W) -> bool) {
+        self.windows.retain(|history| keep(history.window));
+        self.order.retain(|(window, _)| keep(*window));
+        self.redo_order.retain(|(window, _)| keep(*window));
+    }
+
+    fn new_group(&mut self) -> usize {
+        self.next_group += 1;
+        self.next_group
+    }
+
+    fn window_history(&mut self, window: W) -> &mut WindowHistory<W> {
+        match self
+            .windows
+            .iter()
+            .position(|history| history.window == window)
+        {
+            Some(idx) => &mut self.windows[idx],
+            None => {
+                self.windows.push(WindowHistory {
+                    window,
+                    done: VecDeque::new(),
+                    undone: vec![],
+                });
+                self.windows.last_mut().unwrap()
+            }
+        }
+    }
+
+    fn drop_empty(&mut self) {
+        self.windows
+            .retain(|history| !history.done.is_empty() || !history.undone.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32) -> Rect {
+        Rect {
+            x,
+            y: 0,
+            width: 100,
+            height: 100,
+        }
+    }
+
+    fn moves(moves: &[Move<u32>]) -> Vec<(u32, i32, i32)> {
+        moves
+            .iter()
+            .map(|a_move| (a_move.window, a_move.from.x, a_move.to.x))
+            .collect()
+    }
+
+    #[test]
+    fn undoes_across_windows() {
+        let mut history = History::default();
+
+        history.record(1, rect(0), rect(10));
+        history.record(2, rect(0), rect(20));
+        history.record(1, rect(10), rect(30));
+
+        assert_eq!(moves(&history.undo()), vec![(1, 10, 30)]);
+        assert_eq!(moves(&history.undo()), vec![(2, 0, 20)]);
+        assert_eq!(history.last_move(1).map(|a_move| a_move.to), Some(rect(10)));
+
+        assert_eq!(moves(&history.redo()), vec![(2, 0, 20)]);
+        assert_eq!(moves(&history.redo()), vec![(1, 10, 30)]);
+        assert!(history.redo().is_empty());
+    }
+
+    #[test]
+    fn recording_drops_redo() {
+        let mut history = History::default();
+
+        history.record(1, rect(0), rect(10));
+        history.undo();
+        history.record(2, rect(0), rect(20));
+
+        assert!(history.redo().is_empty());
+        assert_eq!(moves(&history.undo()), vec![(2, 0, 20)]);
+        assert!(history.undo().is_empty());
+    }
+
+    #[test]
+    fn ignores_moves_in_place() {
+        let mut history = History::default();
+
+        history.record(1, rect(10), rect(10));
+
+        assert!(history.undo().is_empty());
+        assert_eq!(history.last_move(1), None);
+    }
+
+    #[test]
+    fn groups_undo_together() {
+        let mut history = History::default();
+
+        history.record(3, rect(0), rect(5));
+        history.begin_group();
+        history.record(1, rect(0), rect(10));
+        history.record(2, rect(0), rect(20));
+        history.record(1, rect(10), rect(30));
+        assert_eq!(history.group_windows(), vec![1, 2]);
+        history.end_group();
+
+        assert!(history.group_windows().is_empty());
+        assert_eq!(
+            moves(&history.undo()),
+            vec![(1, 10, 30), (2, 0, 20), (1, 0, 10)]
+        );
+        assert_eq!(
+            moves(&history.redo()),
+            vec![(1, 0, 10), (2, 0, 20), (1, 10, 30)]
+        );
+    }
+
+    #[test]
+    fn keeps_limit() {
+        let mut history = History::with_limit(3);
+
+        for x in 1..=5 {
+            history.record(x as u32 % 2, rect(x - 1), rect(x));
+        }
+
+        assert_eq!(moves(&history.undo()), vec![(1, 4, 5)]);
+        assert_eq!(moves(&history.undo()), vec![(0, 3, 4)]);
+        assert_eq!(moves(&history.undo()), vec![(1, 2, 3)]);
+        assert!(history.undo().is_empty());
+    }
+
+    #[test]
+    fn forgets_closed_windows() {
+        let mut history = History::default();
+
+        history.record(1, rect(0), rect(10));
+        history.record(2, rect(0), rect(20));
+        history.retain(|window| window != 2);
+
+        assert_eq!(moves(&history.undo()), vec![(1, 0, 10)]);
+        assert!(history.undo().is_empty());
+    }
+}
@@ -1,86 +1,340 @@
+use std::io;
 use std::mem;
 use std::ptr;
-use std::thread;
+use std::thread::{self, JoinHandle};
 
+use anyhow::format_err;
+use crossbeam_channel::bounded;
+
+use winapi::shared::minwindef::DWORD;
+use winapi::shared::winerror::ERROR_HOTKEY_ALREADY_REGISTERED;
+use winapi::um::processthreadsapi::GetCurrentThreadId;
 use winapi::um::winuser::{
-    DispatchMessageW, GetKeyboardLayout, GetMessageW, RegisterHotKey, TranslateMessage,
-    VkKeyScanExW, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, WM_HOTKEY,
+    DispatchMessageW, GetKeyboardLayout, GetMessageW, MapVirtualKeyExW, PeekMessageW,
+    PostThreadMessageW, RegisterHotKey, TranslateMessage, UnregisterHotKey, VkKeyScanExW,
+    MAPVK_VSC_TO_VK_EX, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN, PM_NOREMOVE,
+    WM_HOTKEY, WM_QUIT,
 };
 
-use crate::common::report_and_exit;
+use grout::config::Config;
+use grout::geometry::Direction;
+use grout::keys::{Hotkey, Key, Modifiers};
+use grout::Result;
+
+use crate::tray::notify_hotkey_conflicts;
 use crate::Message;
-use crate::CHANNEL;
+use crate::{CHANNEL, HOTKEYS};
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum HotkeyType {
     Main,
     QuickResize,
     Maximize,
+    AutoTile,
+    AutoTileCycle,
+    MasterGrow,
+    MasterShrink,
+    SnapshotSave,
+    SnapshotRestore,
+    Move(Direction),
+    Swap(Direction),
+    RotateForward,
+    RotateBackward,
+    Undo,
+    Redo,
+    StackNext,
+    StackPrevious,
+    Profile(String),
+    Zone(String),
+}
+
+impl HotkeyType {
+    /// What the hotkey does, as listed in the system tray
+    pub fn description(&self) -> String {
+        match self {
+            HotkeyType::Main => "Open grid".to_owned(),
+            HotkeyType::QuickResize => "Quick resize".to_owned(),
+            HotkeyType::Maximize => "Maximize / restore".to_owned(),
+            HotkeyType::AutoTile => "Toggle auto tiling".to_owned(),
+            HotkeyType::AutoTileCycle => "Cycle auto tile layout".to_owned(),
+            HotkeyType::MasterGrow => "Grow master window".to_owned(),
+            HotkeyType::MasterShrink => "Shrink master window".to_owned(),
+            HotkeyType::SnapshotSave => "Save layout".to_owned(),
+            HotkeyType::SnapshotRestore => "Restore layout".to_owned(),
+            HotkeyType::Move(direction) => format!("Move {:?}", direction).to_lowercase(),
+            HotkeyType::Swap(direction) => format!("Swap {:?}", direction).to_lowercase(),
+            HotkeyType::RotateForward => "Rotate forward".to_owned(),
+            HotkeyType::RotateBackward => "Rotate backward".to_owned(),
+            HotkeyType::Undo => "Undo".to_owned(),
+            HotkeyType::Redo => "Redo".to_owned(),
+            HotkeyType::StackNext => "Next window in stack".to_owned(),
+            HotkeyType::StackPrevious => "Previous window in stack".to_owned(),
+            HotkeyType::Profile(profile) => format!("Switch to {}", profile),
+            HotkeyType::Zone(_) => "Send to zone".to_owned(),
+        }
+    }
+}
+
+/// Hotkey from the config along with whether it could be registered
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub hotkey_type: HotkeyType,
+    pub hotkey: String,
+    pub state: BindingState,
+    /// Conflict was shown in a tray notification
+    pub reported: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindingState {
+    Registering,
+    Active,
+    /// Hotkey couldn't be registered for the reason given, so the fallback from the
+    /// config is used instead
+    Fallback {
+        fallback: String,
+        reason: String,
+    },
+    /// Hotkey couldn't be registered for the reason given & there's no fallback, or it
+    /// couldn't be registered either
+    Failed(String),
+}
+
+/// Thread a hotkey is registered on
+pub struct HotkeyThread {
+    thread_id: DWORD,
+    handle: JoinHandle<()>,
+}
+
+/// Every hotkey in the config, along with what it does
+fn bindings(config: &Config) -> Vec<(String, HotkeyType)> {
+    let optional = [
+        (&config.hotkey_quick_resize, HotkeyType::QuickResize),
+        (&config.hotkey_maximize_toggle, HotkeyType::Maximize),
+        (&config.hotkey_auto_tile_toggle, HotkeyType::AutoTile),
+        (&config.hotkey_auto_tile_cycle, HotkeyType::AutoTileCycle),
+        (&config.hotkey_master_grow, HotkeyType::MasterGrow),
+        (&config.hotkey_master_shrink, HotkeyType::MasterShrink),
+        (&config.hotkey_snapshot_save, HotkeyType::SnapshotSave),
+        (&config.hotkey_snapshot_restore, HotkeyType::SnapshotRestore),
+        (&config.hotkey_move_left, HotkeyType::Move(Direction::Left)),
+        (
+            &config.hotkey_move_right,
+            HotkeyType::Move(Direction::Right),
+        ),
+        (&config.hotkey_move_up, HotkeyType::Move(Direction::Up)),
+        (&config.hotkey_move_down, HotkeyType::Move(Direction::Down)),
+        (&config.hotkey_swap_left, HotkeyType::Swap(Direction::Left)),
+        (
+            &config.hotkey_swap_right,
+            HotkeyType::Swap(Direction::Right),
+        ),
+        (&config.hotkey_swap_up, HotkeyType::Swap(Direction::Up)),
+        (&config.hotkey_swap_down, HotkeyType::Swap(Direction::Down)),
+        (&config.hotkey_rotate_forward, HotkeyType::RotateForward),
+        (&config.hotkey_rotate_backward, HotkeyType::RotateBackward),
+        (&config.hotkey_undo, HotkeyType::Undo),
+        (&config.hotkey_redo, HotkeyType::Redo),
+        (&config.hotkey_stack_next, HotkeyType::StackNext),
+        (&config.hotkey_stack_previous, HotkeyType::StackPrevious),
+    ];
+
+    let mut bindings = vec![(config.hotkey.clone(), HotkeyType::Main)];
+
+    for (hotkey, hotkey_type) in optional.iter() {
+        if let Some(hotkey) = hotkey {
+            bindings.push((hotkey.clone(), hotkey_type.clone()));
+        }
+    }
+
+    for (hotkey, profile) in config.profile_hotkeys() {
+        bindings.push((hotkey, HotkeyType::Profile(profile)));
+    }
+
+    let mut zone_hotkeys = config.zone_hotkeys.keys().collect::<Vec<_>>();
+    zone_hotkeys.sort();
+
+    for hotkey in zone_hotkeys {
+        bindings.push((hotkey.clone(), HotkeyType::Zone(hotkey.clone())));
+    }
+
+    bindings
 }
 
-pub fn spawn_hotkey_thread(hotkey_str: &str, hotkey_type: HotkeyType) {
-    let mut hotkey: Vec<String> = hotkey_str
-        .split('+')
-        .map(|s| s.trim().to_string())
+/// Registers every hotkey in the config, each on its own thread. Hotkeys that can't be
+/// registered fall back to the one given under `hotkey_fallbacks`, or get disabled.
+pub fn spawn_hotkey_threads(config: &Config) -> Vec<HotkeyThread> {
+    let bindings = bindings(config);
+
+    *HOTKEYS.lock().unwrap() = bindings
+        .iter()
+        .map(|(hotkey, hotkey_type)| Binding {
+            hotkey_type: hotkey_type.clone(),
+            hotkey: hotkey.clone(),
+            state: BindingState::Registering,
+            reported: false,
+        })
         .collect();
 
-    if hotkey.len() < 2 || hotkey.len() > 5 {
-        report_and_exit(&format!(
-            "Invalid hotkey <{}>: Combination must be between 2 to 5 keys long.",
-            hotkey_str
-        ));
+    bindings
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (hotkey, hotkey_type))| {
+            let fallback = config.fallback_hotkey(&hotkey).map(str::to_owned);
+
+            spawn_hotkey_thread(idx, hotkey, fallback, hotkey_type)
+        })
+        .collect()
+}
+
+/// Unregisters the hotkeys of the threads, returning once every thread has exited so
+/// the same hotkeys can be registered again
+pub fn stop_hotkey_threads(threads: Vec<HotkeyThread>) {
+    for thread in threads {
+        unsafe {
+            PostThreadMessageW(thread.thread_id, WM_QUIT, 0, 0);
+        }
+
+        let _ = thread.handle.join();
     }
+}
+
+/// Whether any hotkey or fallback differs between the configs
+pub fn hotkeys_changed(previous: &Config, config: &Config) -> bool {
+    bindings(previous) != bindings(config) || previous.hotkey_fallbacks != config.hotkey_fallbacks
+}
 
-    let virtual_key_char = hotkey.pop().unwrap().chars().next().unwrap();
+fn spawn_hotkey_thread(
+    idx: usize,
+    hotkey: String,
+    fallback: Option<String>,
+    hotkey_type: HotkeyType,
+) -> HotkeyThread {
+    let (thread_id_sender, thread_id_receiver) = bounded(1);
 
-    let hotkey_str = hotkey_str.to_owned();
-    thread::spawn(move || unsafe {
+    let handle = thread::spawn(move || unsafe {
         let sender = &CHANNEL.0.clone();
 
-        let result = RegisterHotKey(
-            ptr::null_mut(),
-            0,
-            compile_modifiers(&hotkey, &hotkey_str) | MOD_NOREPEAT as u32,
-            get_vkcode(virtual_key_char),
-        );
+        // Creates the message queue of the thread before anyone can ask it to quit
+        let mut msg = mem::zeroed();
+        PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_NOREMOVE);
+        let _ = thread_id_sender.send(GetCurrentThreadId());
+
+        let state = match (register_hotkey(&hotkey), fallback) {
+            (Ok(()), _) => BindingState::Active,
+            (Err(e), Some(fallback)) => match register_hotkey(&fallback) {
+                Ok(()) => BindingState::Fallback {
+                    fallback,
+                    reason: e.to_string(),
+                },
+                Err(fallback_e) => BindingState::Failed(format!("{}, fallback: {}", e, fallback_e)),
+            },
+            (Err(e), None) => BindingState::Failed(e.to_string()),
+        };
+
+        let conflict = state != BindingState::Active;
+        let registered = !matches!(state, BindingState::Failed(_));
 
-        if result == 0 {
-            report_and_exit(&format!("Failed to assign hot key <{}>. Either program is already running or hotkey is already assigned in another program.", hotkey_str));
+        if let Some(binding) = HOTKEYS.lock().unwrap().get_mut(idx) {
+            binding.state = state;
+        }
+
+        if conflict {
+            notify_hotkey_conflicts();
+        }
+
+        if !registered {
+            return;
         }
 
-        let mut msg = mem::zeroed();
         while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) != 0 {
             TranslateMessage(&msg);
             DispatchMessageW(&msg);
 
             if msg.message == WM_HOTKEY {
-                let _ = sender.send(Message::HotkeyPressed(hotkey_type));
+                let _ = sender.send(Message::HotkeyPressed(hotkey_type.clone()));
             }
         }
+
+        UnregisterHotKey(ptr::null_mut(), 0);
     });
-}
 
-fn compile_modifiers(activators: &[String], hotkey_str: &str) -> u32 {
-    let mut code: u32 = 0;
-    for key in activators {
-        match key.as_str() {
-            "ALT" => code |= MOD_ALT as u32,
-            "CTRL" => code |= MOD_CONTROL as u32,
-            "SHIFT" => code |= MOD_SHIFT as u32,
-            "WIN" => code |= MOD_WIN as u32,
-            _ => report_and_exit(&format!("Invalid hotkey <{}>: Unidentified modifier in hotkey combination. Valid modifiers are CTRL, ALT, SHIFT, WIN.", hotkey_str))
-        }
+    HotkeyThread {
+        thread_id: thread_id_receiver.recv().unwrap_or_default(),
+        handle,
     }
-    code
 }
 
-unsafe fn get_vkcode(key_char: char) -> u32 {
-    let keyboard_layout = GetKeyboardLayout(0);
-    let vk_code = VkKeyScanExW(key_char as u16, keyboard_layout);
+/// Registers the hotkey for the calling thread. Fails if the hotkey is invalid, its
+/// key isn't on the keyboard layout, it's already taken or Windows refuses it.
+unsafe fn register_hotkey(text: &str) -> Result<()> {
+    let hotkey = text.parse::<Hotkey>()?;
+
+    let vk_code = key_vkcode(hotkey.key).ok_or_else(|| {
+        format_err!(
+            "<{}> uses {}, which isn't on the keyboard layout",
+            text.trim(),
+            hotkey.key
+        )
+    })?;
 
-    if vk_code == -1 {
-        report_and_exit(&format!("Invalid key {} in hotkey combination.", key_char));
+    let registered = RegisterHotKey(
+        ptr::null_mut(),
+        0,
+        modifier_flags(hotkey.modifiers) | MOD_NOREPEAT as u32,
+        vk_code,
+    );
+
+    if registered == 0 {
+        let error = io::Error::last_os_error();
+
+        return Err(match error.raw_os_error() {
+            Some(code) if code == ERROR_HOTKEY_ALREADY_REGISTERED as i32 => {
+                format_err!("<{}> is taken by another program", text.trim())
+            }
+            _ => format_err!("<{}> couldn't be registered: {}", text.trim(), error),
+        });
     }
 
-    vk_code.to_be_bytes()[1] as u32
+    Ok(())
+}
+
+fn modifier_flags(modifiers: Modifiers) -> u32 {
+    let flags = [
+        (modifiers.alt, MOD_ALT),
+        (modifiers.ctrl, MOD_CONTROL),
+        (modifiers.shift, MOD_SHIFT),
+        (modifiers.win, MOD_WIN),
+    ];
+
+    flags
+        .iter()
+        .filter(|(held, _)| *held)
+        .fold(0, |code, (_, flag)| code | *flag as u32)
+}
+
+/// Virtual key code of the key on the active keyboard layout
+pub fn key_vkcode(key: Key) -> Option<u32> {
+    unsafe {
+        let keyboard_layout = GetKeyboardLayout(0);
+
+        match key {
+            Key::VirtualKey(vk_code) => Some(vk_code as u32),
+            Key::Char(key_char) => {
+                let vk_code = VkKeyScanExW(key_char as u16, keyboard_layout);
+
+                if vk_code == -1 {
+                    None
+                } else {
+                    Some(vk_code.to_be_bytes()[1] as u32)
+                }
+            }
+            Key::ScanCode(code) => {
+                match MapVirtualKeyExW(code as u32, MAPVK_VSC_TO_VK_EX, keyboard_layout) {
+                    0 => None,
+                    vk_code => Some(vk_code),
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,443 @@
+//! Hotkeys such as `CTRL+ALT+S`, parsed into modifiers & a key.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::format_err;
+
+use crate::{Error, Result};
+
+/// Keys held down along with the key of a hotkey
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub win: bool,
+}
+
+/// Key pressed to trigger a hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// Windows virtual key code, such as `0x70` for F1
+    VirtualKey(u8),
+    /// Character typed by the key, found on the active keyboard layout, such as `;`
+    Char(char),
+    /// Hardware scan code, the same physical key on every keyboard layout. Extended
+    /// keys have `0xE000` added, such as `0xE048` for the up arrow.
+    ScanCode(u16),
+}
+
+/// Combination of modifiers & a key, such as `CTRL+ALT+F12`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+/// Names of the keys that aren't letters, digits, F1 - F24 or NUMPAD0 - NUMPAD9, along
+/// with their virtual key code. The first name of a key is the one it's shown with.
+const NAMED_KEYS: &[(&str, u8)] = &[
+    ("BACKSPACE", 0x08),
+    ("BACK", 0x08),
+    ("TAB", 0x09),
+    ("CLEAR", 0x0C),
+    ("ENTER", 0x0D),
+    ("RETURN", 0x0D),
+    ("PAUSE", 0x13),
+    ("CAPSLOCK", 0x14),
+    ("ESCAPE", 0x1B),
+    ("ESC", 0x1B),
+    ("SPACE", 0x20),
+    ("PAGEUP", 0x21),
+    ("PGUP", 0x21),
+    ("PAGEDOWN", 0x22),
+    ("PGDN", 0x22),
+    ("END", 0x23),
+    ("HOME", 0x24),
+    ("LEFT", 0x25),
+    ("UP", 0x26),
+    ("RIGHT", 0x27),
+    ("DOWN", 0x28),
+    ("PRINTSCREEN", 0x2C),
+    ("PRTSC", 0x2C),
+    ("INSERT", 0x2D),
+    ("INS", 0x2D),
+    ("DELETE", 0x2E),
+    ("DEL", 0x2E),
+    ("APPS", 0x5D),
+    ("NUMPADMULT", 0x6A),
+    ("MULTIPLY", 0x6A),
+    ("NUMPADADD", 0x6B),
+    ("ADD", 0x6B),
+    ("SEPARATOR", 0x6C),
+    ("NUMPADSUB", 0x6D),
+    ("SUBTRACT", 0x6D),
+    ("NUMPADDOT", 0x6E),
+    ("DECIMAL", 0x6E),
+    ("NUMPADDIV", 0x6F),
+    ("DIVIDE", 0x6F),
+    ("NUMLOCK", 0x90),
+    ("SCROLLLOCK", 0x91),
+    ("BROWSER_BACK", 0xA6),
+    ("BROWSER_FORWARD", 0xA7),
+    ("VOLUME_MUTE", 0xAD),
+    ("VOLUME_DOWN", 0xAE),
+    ("VOLUME_UP", 0xAF),
+    ("MEDIA_NEXT", 0xB0),
+    ("MEDIA_PREV", 0xB1),
+    ("MEDIA_STOP", 0xB2),
+    ("MEDIA_PLAY_PAUSE", 0xB3),
+    ("OEM_1", 0xBA),
+    ("OEM_PLUS", 0xBB),
+    ("PLUS", 0xBB),
+    ("OEM_COMMA", 0xBC),
+    ("OEM_MINUS", 0xBD),
+    ("OEM_PERIOD", 0xBE),
+    ("OEM_2", 0xBF),
+    ("OEM_3", 0xC0),
+    ("OEM_4", 0xDB),
+    ("OEM_5", 0xDC),
+    ("OEM_6", 0xDD),
+    ("OEM_7", 0xDE),
+    ("OEM_8", 0xDF),
+    ("OEM_102", 0xE2),
+];
+
+const VK_F1: u8 = 0x70;
+const VK_NUMPAD0: u8 = 0x60;
+
+const KEY_HELP: &str = "Keys are letters, digits, F1 - F24, NUMPAD0 - NUMPAD9, named keys \
+                        such as LEFT, SPACE or OEM_1, scan codes such as SC029 and virtual \
+                        key codes such as VK7B";
+
+impl FromStr for Key {
+    type Err = Error;
+
+    /// Parses a key name, ignoring case
+    fn from_str(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let name = text.to_uppercase();
+
+        let mut chars = text.chars();
+        if let (Some(key_char), None) = (chars.next(), chars.next()) {
+            return Ok(match key_char.to_ascii_uppercase() {
+                key @ 'A'..='Z' | key @ '0'..='9' => Key::VirtualKey(key as u8),
+                _ => Key::Char(key_char),
+            });
+        }
+
+        if let Some((_, vk_code)) = NAMED_KEYS.iter().find(|(key, _)| *key == name) {
+            return Ok(Key::VirtualKey(*vk_code));
+        }
+
+        if let Some(number @ 1..=24) = numbered(&name, "F") {
+            return Ok(Key::VirtualKey(VK_F1 + number - 1));
+        }
+
+        if let Some(number @ 0..=9) = numbered(&name, "NUMPAD") {
+            return Ok(Key::VirtualKey(VK_NUMPAD0 + number));
+        }
+
+        if let Some(code) = hex(&name, "SC") {
+            return match code {
+                0x01..=0xFF => Ok(Key::ScanCode(code)),
+                0x101..=0x1FF => Ok(Key::ScanCode(0xE000 | (code & 0xFF))),
+                _ => Err(format_err!(
+                    "Scan code {} is out of range, expected SC001 - SC1FF",
+                    text
+                )),
+            };
+        }
+
+        if let Some(code) = hex(&name, "VK") {
+            return match code {
+                0x01..=0xFE => Ok(Key::VirtualKey(code as u8)),
+                _ => Err(format_err!(
+                    "Virtual key code {} is out of range, expected VK01 - VKFE",
+                    text
+                )),
+            };
+        }
+
+        Err(format_err!("Unknown key {}. {}", text, KEY_HELP))
+    }
+}
+
+/// Names of the keys & modifiers hotkeys are written with, other than letters, digits,
+/// scan codes & virtual key codes
+pub fn key_names() -> Vec<String> {
+    let modifiers = ["CTRL", "CONTROL", "ALT", "SHIFT", "WIN"];
+    let function_keys = (1..=24).map(|number| format!("F{}", number));
+    let numpad_keys = (0..=9).map(|number| format!("NUMPAD{}", number));
+
+    modifiers
+        .iter()
+        .chain(NAMED_KEYS.iter().map(|(name, _)| name))
+        .map(|name| name.to_string())
+        .chain(function_keys)
+        .chain(numpad_keys)
+        .collect()
+}
+
+/// Number following `prefix`, such as 12 in F12
+fn numbered(name: &str, prefix: &str) -> Option<u8> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+/// Hexadecimal code following `prefix`, such as 0x29 in SC029
+fn hex(name: &str, prefix: &str) -> Option<u16> {
+    let digits = name.strip_prefix(prefix)?;
+
+    if digits.is_empty() || digits.len() > 4 {
+        return None;
+    }
+
+    u16::from_str_radix(digits, 16).ok()
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Key::VirtualKey(vk_code @ b'0'..=b'9') | Key::VirtualKey(vk_code @ b'A'..=b'Z') => {
+                write!(f, "{}", vk_code as char)
+            }
+            Key::VirtualKey(vk_code) if (VK_F1..VK_F1 + 24).contains(&vk_code) => {
+                write!(f, "F{}", vk_code - VK_F1 + 1)
+            }
+            Key::VirtualKey(vk_code) if (VK_NUMPAD0..VK_NUMPAD0 + 10).contains(&vk_code) => {
+                write!(f, "NUMPAD{}", vk_code - VK_NUMPAD0)
+            }
+            Key::VirtualKey(vk_code) => {
+                match NAMED_KEYS.iter().find(|(_, named)| *named == vk_code) {
+                    Some((name, _)) => write!(f, "{}", name),
+                    None => write!(f, "VK{:02X}", vk_code),
+                }
+            }
+            Key::Char(key_char) => write!(f, "{}", key_char),
+            Key::ScanCode(code) if code & 0xE000 == 0xE000 => {
+                write!(f, "SC{:03X}", 0x100 | (code & 0xFF))
+            }
+            Key::ScanCode(code) => write!(f, "SC{:03X}", code),
+        }
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = Error;
+
+    /// Parses modifiers followed by a key, joined by `+`, such as `CTRL+ALT+F12`.
+    /// At least one of the modifiers CTRL, ALT, SHIFT & WIN is needed.
+    fn from_str(text: &str) -> Result<Self> {
+        parse_hotkey(text).map_err(|e| format_err!("Invalid hotkey <{}>: {}", text.trim(), e))
+    }
+}
+
+fn parse_hotkey(text: &str) -> Result<Hotkey> {
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+
+    let key = match parts.pop() {
+        Some("") if parts.last() == Some(&"") => {
+            return Err(format_err!("use PLUS for the + key"));
+        }
+        Some("") | None => return Err(format_err!("missing the key after the modifiers")),
+        Some(key) => key,
+    };
+
+    if is_modifier(key) {
+        return Err(format_err!(
+            "ends in the modifier {}, expected a key after it",
+            key
+        ));
+    }
+
+    let key = key.parse::<Key>()?;
+
+    if parts.is_empty() {
+        return Err(format_err!(
+            "at least one modifier is needed. Valid modifiers are CTRL, ALT, SHIFT, WIN"
+        ));
+    }
+
+    let mut modifiers = Modifiers::default();
+
+    for part in parts {
+        let held = match part.to_uppercase().as_str() {
+            "CTRL" | "CONTROL" => &mut modifiers.ctrl,
+            "ALT" => &mut modifiers.alt,
+            "SHIFT" => &mut modifiers.shift,
+            "WIN" => &mut modifiers.win,
+            _ if part.parse::<Key>().is_ok() => {
+                return Err(format_err!(
+                    "{} isn't a modifier, only the last key can be one other than CTRL, ALT, SHIFT or WIN",
+                    part
+                ));
+            }
+            _ => {
+                return Err(format_err!(
+                    "unknown modifier {}. Valid modifiers are CTRL, ALT, SHIFT, WIN",
+                    part
+                ));
+            }
+        };
+
+        if *held {
+            return Err(format_err!("{} is given more than once", part));
+        }
+
+        *held = true;
+    }
+
+    Ok(Hotkey { modifiers, key })
+}
+
+fn is_modifier(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().as_str(),
+        "CTRL" | "CONTROL" | "ALT" | "SHIFT" | "WIN"
+    )
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "CTRL"),
+            (self.modifiers.alt, "ALT"),
+            (self.modifiers.shift, "SHIFT"),
+            (self.modifiers.win, "WIN"),
+        ];
+
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{}+", name)?;
+        }
+
+        write!(f, "{}", self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkey(text: &str) -> Hotkey {
+        text.parse().unwrap()
+    }
+
+    fn error(text: &str) -> String {
+        text.parse::<Hotkey>().unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        let parsed = hotkey("ctrl + Alt+SHIFT+win+A");
+
+        assert_eq!(
+            parsed.modifiers,
+            Modifiers {
+                ctrl: true,
+                alt: true,
+                shift: true,
+                win: true,
+            }
+        );
+        assert_eq!(parsed.key, Key::VirtualKey(b'A'));
+
+        assert!(hotkey("CONTROL+S").modifiers.ctrl);
+        assert_eq!(
+            hotkey("ALT+1").modifiers,
+            Modifiers {
+                alt: true,
+                ..Modifiers::default()
+            }
+        );
+    }
+
+    #[test]
+    fn parses_function_keys() {
+        assert_eq!(hotkey("CTRL+F1").key, Key::VirtualKey(0x70));
+        assert_eq!(hotkey("CTRL+f12").key, Key::VirtualKey(0x7B));
+        assert_eq!(hotkey("CTRL+F24").key, Key::VirtualKey(0x87));
+
+        assert!(error("CTRL+F0").contains("Unknown key F0"));
+        assert!(error("CTRL+F25").contains("Unknown key F25"));
+    }
+
+    #[test]
+    fn parses_numpad_keys() {
+        assert_eq!(hotkey("CTRL+NUMPAD0").key, Key::VirtualKey(0x60));
+        assert_eq!(hotkey("CTRL+numpad9").key, Key::VirtualKey(0x69));
+        assert_eq!(hotkey("CTRL+NUMPADADD").key, Key::VirtualKey(0x6B));
+
+        assert!(error("CTRL+NUMPAD10").contains("Unknown key NUMPAD10"));
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert_eq!(hotkey("CTRL+OEM_1").key, Key::VirtualKey(0xBA));
+        assert_eq!(hotkey("CTRL+oem_102").key, Key::VirtualKey(0xE2));
+        assert_eq!(hotkey("CTRL+PLUS").key, Key::VirtualKey(0xBB));
+        assert_eq!(hotkey("CTRL+Esc").key, Key::VirtualKey(0x1B));
+        assert_eq!(hotkey("CTRL+LEFT").key, Key::VirtualKey(0x25));
+
+        assert!(error("CTRL+OEM_9").contains("Unknown key OEM_9"));
+    }
+
+    #[test]
+    fn parses_characters() {
+        assert_eq!(hotkey("CTRL+;").key, Key::Char(';'));
+        assert_eq!(hotkey("CTRL+é").key, Key::Char('é'));
+        assert_eq!(hotkey("CTRL+a").key, Key::VirtualKey(b'A'));
+        assert_eq!(hotkey("CTRL+-").key, Key::Char('-'));
+    }
+
+    #[test]
+    fn parses_scan_codes() {
+        assert_eq!(hotkey("CTRL+SC029").key, Key::ScanCode(0x29));
+        assert_eq!(hotkey("CTRL+sc148").key, Key::ScanCode(0xE048));
+
+        assert!(error("CTRL+SC000").contains("out of range"));
+        assert!(error("CTRL+SC200").contains("out of range"));
+    }
+
+    #[test]
+    fn parses_virtual_key_codes() {
+        assert_eq!(hotkey("CTRL+VK7B").key, Key::VirtualKey(0x7B));
+
+        assert!(error("CTRL+VK00").contains("out of range"));
+        assert!(error("CTRL+VKFF").contains("out of range"));
+    }
+
+    #[test]
+    fn rejects_invalid_hotkeys() {
+        assert!(error("A").contains("at least one modifier is needed"));
+        assert!(error("CTRL+").contains("missing the key"));
+        assert!(error("CTRL++").contains("use PLUS"));
+        assert!(error("CTRL+ALT").contains("ends in the modifier ALT"));
+        assert!(error("CTRL+CTRL+A").contains("CTRL is given more than once"));
+        assert!(error("A+B").contains("A isn't a modifier"));
+        assert!(error("HYPER+A").contains("unknown modifier HYPER"));
+        assert!(error("CTRL+NOPE").contains("Unknown key NOPE"));
+        assert!(error(" CTRL+NOPE ").starts_with("Invalid hotkey <CTRL+NOPE>"));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let hotkeys = [
+            "CTRL+ALT+S",
+            "WIN+F24",
+            "SHIFT+NUMPAD7",
+            "CTRL+OEM_PLUS",
+            "ALT+;",
+            "CTRL+SC029",
+            "CTRL+SC148",
+            "CTRL+VK07",
+            "CTRL+ALT+SHIFT+WIN+BACKSPACE",
+        ];
+
+        for text in hotkeys.iter() {
+            assert_eq!(hotkey(text).to_string(), *text);
+        }
+
+        assert_eq!(hotkey("win+shift+pgup").to_string(), "SHIFT+WIN+PAGEUP");
+    }
+}
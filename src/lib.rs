@@ -0,0 +1,38 @@
+//! Layout engine behind grout: grid & zone math, config parsing and the grid cache.
+//!
+//! Everything here is platform independent, so zones can be computed by other tools,
+//! e.g. a launch script placing windows or a status bar showing the active layout,
+//! without running the Windows UI.
+//!
+//! - [`config::load_config`] reads `config.toml`, `config.json` or `config.yml`
+//! - [`grid_config::GridConfig::zones`] gives the area of each tile of a grid
+//! - [`config::Config::layout_areas`] gives the area of each zone of a custom layout
+//! - [`cache::read_cache`] reads the grids remembered for each monitor & profile
+//! - [`check::check_config`] finds every problem in a config file, with its line & column
+
+use std::result;
+
+use anyhow::Error;
+
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod dpi;
+pub mod geometry;
+pub mod grid_config;
+pub mod history;
+pub mod keys;
+mod locate;
+pub mod monitor_id;
+mod rect;
+pub mod rules;
+pub mod snapshot;
+pub mod spacing;
+pub mod stack;
+pub mod theme;
+pub mod tiling;
+
+pub use rect::Rect;
+
+/// Result of any fallible function of the library
+pub type Result<T> = result::Result<T, Error>;
@@ -0,0 +1,427 @@
+//! Where settings are written in a config file, so problems can point at them and
+//! settings can be changed without rewriting the rest of the file
+
+use std::collections::HashMap;
+
+use config::FileFormat;
+
+/// Line & byte offset of the setting at `path`, if the text has it
+pub(crate) fn key_position(
+    text: &str,
+    format: FileFormat,
+    path: &[String],
+) -> Option<(usize, usize)> {
+    locate(text, format)
+        .into_iter()
+        .find(|(located_path, _)| located_path[..] == path[..])
+        .map(|(_, position)| position)
+}
+
+/// Path of every key & list item in the file, along with its line & byte offset, in
+/// the order they're written. Paths follow the nesting of the file, so a key is only
+/// found under the tables it's written in.
+pub(crate) fn locate(text: &str, format: FileFormat) -> Vec<(Vec<String>, (usize, usize))> {
+    let mut scanner = Scanner {
+        lines: text.lines().collect(),
+        line: 0,
+        byte: 0,
+        format,
+        located: vec![],
+    };
+
+    match format {
+        FileFormat::Toml => scanner.toml(),
+        FileFormat::Yaml => scanner.yaml(),
+        _ => scanner.flow_value(&[]),
+    }
+
+    scanner.located
+}
+
+/// `path` followed by `key`
+pub(crate) fn child_path(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_owned());
+
+    path
+}
+
+/// Walks through a config file, keeping track of where each key & list item is
+struct Scanner<'a> {
+    lines: Vec<&'a str>,
+    line: usize,
+    byte: usize,
+    format: FileFormat,
+    located: Vec<(Vec<String>, (usize, usize))>,
+}
+
+impl<'a> Scanner<'a> {
+    /// Rest of the current line
+    fn rest(&self) -> &'a str {
+        self.lines
+            .get(self.line)
+            .map_or("", |line| &line[self.byte..])
+    }
+
+    fn peek(&self) -> Option<char> {
+        if self.line >= self.lines.len() {
+            return None;
+        }
+
+        Some(self.rest().chars().next().unwrap_or('\n'))
+    }
+
+    fn bump(&mut self) {
+        match self.rest().chars().next() {
+            Some(c) => self.byte += c.len_utf8(),
+            None => self.next_line(),
+        }
+    }
+
+    fn next_line(&mut self) {
+        self.line += 1;
+        self.byte = 0;
+    }
+
+    fn at(&self) -> (usize, usize) {
+        (self.line, self.byte)
+    }
+
+    fn found(&mut self, path: Vec<String>, at: (usize, usize)) {
+        self.located.push((path, at));
+    }
+
+    fn is_comment(&self) -> bool {
+        self.format != FileFormat::Json && self.rest().starts_with('#')
+    }
+
+    /// Skips spaces on the current line, along with a comment ending it
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.byte += rest.len() - rest.trim_start().len();
+
+        if self.is_comment() {
+            self.byte = self.lines[self.line].len();
+        }
+    }
+
+    /// Skips spaces, comments & line breaks
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+
+            match self.peek() {
+                Some('\n') => self.next_line(),
+                _ => return,
+            }
+        }
+    }
+
+    /// Reads a quoted string, returning what's between the quotes
+    fn string(&mut self) -> String {
+        let quote = match self.peek() {
+            Some(quote @ '"') | Some(quote @ '\'') => quote,
+            _ => return String::new(),
+        };
+
+        // Multiline TOML strings are only skipped, since they're never keys
+        let triple = quote.to_string().repeat(3);
+        if self.format == FileFormat::Toml && self.rest().starts_with(&triple) {
+            self.byte += 3;
+
+            while self.peek().is_some() && !self.rest().starts_with(&triple) {
+                self.bump();
+            }
+
+            if self.peek().is_some() {
+                self.byte += 3;
+            }
+
+            return String::new();
+        }
+
+        self.bump();
+
+        let mut text = String::new();
+        let mut escaped = false;
+
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                // Strings only go on over several lines in YAML
+                if self.format != FileFormat::Yaml {
+                    break;
+                }
+            } else if escaped {
+                escaped = false;
+            } else if c == '\\' && quote == '"' {
+                escaped = true;
+            } else if c == quote {
+                self.bump();
+
+                // Quotes are doubled inside single quoted YAML strings
+                if self.format == FileFormat::Yaml && quote == '\'' && self.peek() == Some('\'') {
+                    self.bump();
+                    text.push(c);
+                    continue;
+                }
+
+                return text;
+            }
+
+            text.push(c);
+            self.bump();
+        }
+
+        text
+    }
+
+    /// Reads a key ending before one of `ends`, which may be quoted
+    fn key(&mut self, ends: &[char]) -> String {
+        if matches!(self.peek(), Some('"') | Some('\'')) {
+            return self.string();
+        }
+
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| ends.contains(&c) || (c == '#' && self.format != FileFormat::Json))
+            .unwrap_or(rest.len());
+        self.byte += len;
+
+        rest[..len].trim().to_owned()
+    }
+
+    /// Value written inline, which may be a table in braces or a list in brackets
+    /// going on over several lines
+    fn flow_value(&mut self, path: &[String]) {
+        self.skip_blank();
+
+        match self.peek() {
+            Some('{') => self.flow_collection(path, '}'),
+            Some('[') => self.flow_collection(path, ']'),
+            Some('"') | Some('\'') => {
+                self.string();
+            }
+            _ => {
+                let rest = self.rest();
+                let len = rest.find(&[',', '}', ']'][..]).unwrap_or(rest.len());
+                self.byte += len;
+            }
+        }
+    }
+
+    /// Entries of a table, or items of a list, up to the closing `close`
+    fn flow_collection(&mut self, path: &[String], close: char) {
+        self.bump();
+
+        let mut idx = 0;
+
+        loop {
+            self.skip_blank();
+
+            let at = self.at();
+            let child = match self.peek() {
+                None => return,
+                Some(c) if c == close => {
+                    self.bump();
+                    return;
+                }
+                Some('}') | Some(']') => return,
+                Some(',') => {
+                    self.bump();
+                    continue;
+                }
+                Some(_) if close == ']' => {
+                    idx += 1;
+                    child_path(path, &format!("[{}]", idx - 1))
+                }
+                Some(_) => {
+                    let key = self.key(&[':', '=', ',', '}', ']']);
+
+                    self.skip_blank();
+                    if matches!(self.peek(), Some(':') | Some('=')) {
+                        self.bump();
+                    }
+
+                    child_path(path, &key)
+                }
+            };
+
+            self.found(child.clone(), at);
+            self.flow_value(&child);
+
+            // Skips what couldn't be read, so that a broken value can't stall the scan
+            if self.at() == at {
+                self.bump();
+            }
+        }
+    }
+
+    /// Keys joined by `.`, each found along with where it starts
+    fn dotted_key(&mut self) -> Vec<(String, (usize, usize))> {
+        let mut keys = vec![];
+
+        loop {
+            self.skip_spaces();
+
+            let at = self.at();
+            keys.push((self.key(&['.', '=', ']']), at));
+
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return keys;
+            }
+
+            self.bump();
+        }
+    }
+
+    /// Finds each of the keys under the table at `path`, returning the path they lead to
+    fn found_keys(&mut self, path: &[String], keys: Vec<(String, (usize, usize))>) -> Vec<String> {
+        let mut path = path.to_vec();
+
+        for (key, at) in keys {
+            path.push(key);
+            self.found(path.clone(), at);
+        }
+
+        path
+    }
+
+    /// TOML file, made up of `key = value` lines under `[table]` & `[[list]]` headers
+    fn toml(&mut self) {
+        let mut table = vec![];
+        let mut list_items = HashMap::<Vec<String>, usize>::new();
+
+        loop {
+            self.skip_blank();
+
+            let at = self.at();
+
+            match self.peek() {
+                None => return,
+                Some('[') if self.rest().starts_with("[[") => {
+                    self.byte += 2;
+
+                    let keys = self.dotted_key();
+                    let list = self.found_keys(&[], keys);
+                    let idx = list_items.entry(list.clone()).or_insert(0);
+
+                    table = child_path(&list, &format!("[{}]", idx));
+                    *idx += 1;
+
+                    self.found(table.clone(), at);
+                }
+                Some('[') => {
+                    self.bump();
+
+                    let keys = self.dotted_key();
+                    table = self.found_keys(&[], keys);
+                }
+                Some(_) => {
+                    let keys = self.dotted_key();
+                    let path = self.found_keys(&table, keys);
+
+                    self.skip_spaces();
+                    if self.peek() == Some('=') {
+                        self.bump();
+                        self.flow_value(&path);
+                    }
+                }
+            }
+
+            if self.line == at.0 {
+                self.next_line();
+            }
+        }
+    }
+
+    /// YAML file, nested by indentation, with items of a list each starting with `-`
+    fn yaml(&mut self) {
+        // Tables & items the current line may be in, along with their indentation
+        let mut parents: Vec<(usize, Vec<String>, bool)> = vec![];
+        let mut list_items = HashMap::<Vec<String>, usize>::new();
+
+        while self.line < self.lines.len() {
+            let line = self.line;
+            self.skip_spaces();
+
+            let rest = self.rest();
+            if rest.is_empty() || rest.starts_with("---") {
+                self.next_line();
+                continue;
+            }
+
+            while self.rest() == "-" || self.rest().starts_with("- ") {
+                let indent = self.byte;
+
+                while matches!(parents.last(), Some((parent_indent, _, is_item))
+                    if *parent_indent > indent || (*parent_indent == indent && *is_item))
+                {
+                    parents.pop();
+                }
+
+                let list = parents.last().map(|(_, path, _)| path.clone());
+                let list = list.unwrap_or_default();
+                let idx = list_items.entry(list.clone()).or_insert(0);
+                let item = child_path(&list, &format!("[{}]", idx));
+                *idx += 1;
+
+                self.found(item.clone(), self.at());
+                parents.push((indent, item, true));
+
+                self.bump();
+                self.skip_spaces();
+            }
+
+            let indent = self.byte;
+
+            while matches!(parents.last(), Some((parent_indent, _, _)) if *parent_indent >= indent)
+            {
+                parents.pop();
+            }
+
+            let parent = parents.last().map(|(_, path, _)| path.clone());
+            let parent = parent.unwrap_or_default();
+
+            match self.peek() {
+                Some('{') | Some('[') => self.flow_value(&parent),
+                Some(_) if self.is_yaml_key() => {
+                    let at = self.at();
+                    let path = child_path(&parent, &self.key(&[':']));
+
+                    self.found(path.clone(), at);
+                    parents.push((indent, path.clone(), false));
+
+                    self.skip_spaces();
+                    self.bump();
+                    self.skip_spaces();
+
+                    if matches!(self.peek(), Some('{') | Some('[')) {
+                        self.flow_value(&path);
+                    }
+                }
+                _ => {}
+            }
+
+            if self.line == line {
+                self.next_line();
+            }
+        }
+    }
+
+    /// Rest of the line starts with a key followed by `:`
+    fn is_yaml_key(&self) -> bool {
+        let rest = self.rest();
+        let is_key_end = |end: &str| end.is_empty() || end.starts_with(char::is_whitespace);
+
+        match rest.chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => matches!(
+                rest[1..].find(quote),
+                Some(end) if rest[end + 2..].trim_start().starts_with(':')
+            ),
+            _ => rest
+                .match_indices(':')
+                .any(|(idx, _)| is_key_end(&rest[idx + 1..]) && !rest[..idx].contains(" #")),
+        }
+    }
+}
@@ -1,47 +1,111 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(non_snake_case)]
 
+use std::env;
+use std::path::PathBuf;
+use std::process;
+#[cfg(windows)]
 use std::{
-    mem, result,
+    mem,
     sync::{Arc, Mutex},
 };
 
-use anyhow::Error;
+#[cfg(windows)]
 use crossbeam_channel::{bounded, select, unbounded, Receiver, Sender};
+#[cfg(windows)]
 use lazy_static::lazy_static;
 
+#[cfg(windows)]
+use winapi::shared::windef::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
+#[cfg(windows)]
+use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+#[cfg(windows)]
 use winapi::um::winuser::{
-    SetForegroundWindow, ShowWindow, TrackMouseEvent, SW_SHOW, TME_LEAVE, TRACKMOUSEEVENT,
+    SetForegroundWindow, SetProcessDpiAwarenessContext, ShowWindow, TrackMouseEvent, SW_SHOW,
+    TME_LEAVE, TRACKMOUSEEVENT,
 };
 
-use crate::common::{get_foreground_window, report_and_exit, show_msg_box, Rect};
-use crate::event::{spawn_foreground_hook, spawn_track_monitor_thread};
+#[cfg(windows)]
+use grout::cache::{cache_dir, GridCache, SAVE_DELAY};
+use grout::check::check_config;
+use grout::config;
+#[cfg(windows)]
+use grout::history::History;
+#[cfg(windows)]
+use grout::snapshot::{Snapshot, DEFAULT_SNAPSHOT};
+#[cfg(windows)]
+use grout::stack::Stacks;
+#[cfg(windows)]
+use grout::Rect;
+
+#[cfg(windows)]
+use crate::autotile::AutoTile;
+#[cfg(windows)]
+use crate::common::{get_foreground_window, report_and_exit, show_msg_box};
+#[cfg(windows)]
+use crate::desktop::{capture_snapshot, restore_snapshot};
+#[cfg(windows)]
+use crate::event::{
+    spawn_config_watcher, spawn_foreground_hook, spawn_track_monitor_thread, spawn_window_hook,
+};
+#[cfg(windows)]
 use crate::grid::Grid;
-use crate::hotkey::{spawn_hotkey_thread, HotkeyType};
-use crate::tray::spawn_sys_tray;
-use crate::window::{spawn_grid_window, spawn_preview_window, Window};
-
+#[cfg(windows)]
+use crate::hotkey::{
+    hotkeys_changed, spawn_hotkey_threads, stop_hotkey_threads, Binding, HotkeyType,
+};
+#[cfg(windows)]
+use crate::placement::{move_window, rotate_windows, swap_window, RulePlacer, ZoneHotkeys};
+#[cfg(windows)]
+use crate::tabs::TabStrips;
+#[cfg(windows)]
+use crate::tray::{notify, spawn_sys_tray};
+#[cfg(windows)]
+use crate::window::{set_preview_color, spawn_grid_window, spawn_preview_window, Window};
+
+#[cfg(windows)]
 mod autostart;
+#[cfg(windows)]
+mod autotile;
+#[cfg(windows)]
 mod common;
-mod config;
+#[cfg(windows)]
+mod desktop;
+#[cfg(windows)]
 mod event;
+#[cfg(windows)]
 mod grid;
+#[cfg(windows)]
 mod hotkey;
+#[cfg(windows)]
+mod monitor;
+#[cfg(windows)]
+mod placement;
+#[cfg(windows)]
+mod tabs;
+#[cfg(windows)]
 mod tray;
+#[cfg(windows)]
 mod window;
 
+#[cfg(windows)]
 lazy_static! {
     static ref CHANNEL: (Sender<Message>, Receiver<Message>) = unbounded();
     static ref CONFIG: Arc<Mutex<config::Config>> = {
         match config::load_config() {
             Ok(config) => Arc::new(Mutex::new(config)),
-            Err(e) => report_and_exit(&format!("Could not load config. Check config file for formatting errors and relaunch program.\n\nErr: {}", e)),
+            Err(e) => report_and_exit(&format!("Could not load config. Check config file for formatting errors and relaunch program. Run grout --check-config to list every problem.\n\nErr: {}", e)),
         }
     };
+    static ref GRID_CACHE: GridCache = GridCache::open(cache_dir(), SAVE_DELAY);
     static ref GRID: Arc<Mutex<Grid>> = Arc::new(Mutex::new(Grid::from(&*CONFIG.lock().unwrap())));
+    static ref HISTORY: Arc<Mutex<History<Window>>> = Arc::new(Mutex::new(History::default()));
+    static ref STACKS: Arc<Mutex<Stacks<Window>>> = Arc::new(Mutex::new(Stacks::default()));
     static ref ACTIVE_PROFILE: Arc<Mutex<String>> = Arc::new(Mutex::new("Default".to_owned()));
+    static ref HOTKEYS: Arc<Mutex<Vec<Binding>>> = Arc::new(Mutex::new(vec![]));
 }
 
+#[cfg(windows)]
 pub enum Message {
     PreviewWindow(Window),
     GridWindow(Window),
@@ -49,8 +113,18 @@ pub enum Message {
     HotkeyPressed(HotkeyType),
     TrackMouse(Window),
     ActiveWindowChange(Window),
-    ProfileChange(&'static str),
+    ProfileChange(String),
+    ConfigChanged,
     MonitorChange,
+    WindowsChanged,
+    WindowShown(Window),
+    WindowFocused(Window),
+    WindowMoved,
+    TabStrip(usize, Window),
+    StacksChanged,
+    GridChanged,
+    SaveSnapshot(String),
+    RestoreSnapshot(String),
     MouseLeft,
     InitializeWindows,
     CloseWindows,
@@ -66,9 +140,23 @@ macro_rules! str_to_wide {
     }};
 }
 
-pub type Result<T> = result::Result<T, Error>;
-
+#[cfg(windows)]
 fn main() {
+    if let Some(path) = check_config_arg() {
+        // Release builds have no console of their own to print to
+        unsafe {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+
+        run_check_config(path);
+    }
+
+    // Work areas & window positions are in physical pixels on every monitor, grid sizes
+    // from the config get scaled by each monitor's DPI
+    unsafe {
+        SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+    }
+
     let receiver = &CHANNEL.1.clone();
     let sender = &CHANNEL.0.clone();
 
@@ -85,20 +173,25 @@ fn main() {
         };
     }
 
-    spawn_hotkey_thread(&config.hotkey, HotkeyType::Main);
+    let mut hotkey_threads = spawn_hotkey_threads(&config);
 
-    if let Some(hotkey) = &config.hotkey_quick_resize {
-        spawn_hotkey_thread(hotkey, HotkeyType::QuickResize);
-    }
+    spawn_window_hook();
 
-    if let Some(hotkey_maximize) = &config.hotkey_maximize_toggle {
-        spawn_hotkey_thread(hotkey_maximize, HotkeyType::Maximize);
-    }
+    spawn_config_watcher();
 
     unsafe {
         spawn_sys_tray();
     }
 
+    let mut auto_tile = AutoTile::from(&config);
+    auto_tile.apply(&config);
+
+    let mut rule_placer = RulePlacer::from(&config);
+
+    let mut zone_hotkeys = ZoneHotkeys::default();
+
+    let mut tab_strips = TabStrips::default();
+
     let mut preview_window: Option<Window> = None;
     let mut grid_window: Option<Window> = None;
     let mut track_mouse = false;
@@ -123,6 +216,9 @@ fn main() {
                         grid.grid_window = Some(window);
                         grid.active_window = Some(get_foreground_window());
 
+                        // Every placement until the grid closes is undone as one
+                        HISTORY.lock().unwrap().begin_group();
+
                         spawn_track_monitor_thread(close_channel.1.clone());
                         spawn_preview_window(close_channel.1.clone());
                     }
@@ -132,8 +228,8 @@ fn main() {
 
                         preview_window.set_pos(rect, Some(grid_window));
                     }
-                    Message::HotkeyPressed(hotkey_type) => {
-                        if hotkey_type == HotkeyType::Maximize {
+                    Message::HotkeyPressed(hotkey_type) => match hotkey_type {
+                        HotkeyType::Maximize => {
                             let mut grid = GRID.lock().unwrap();
 
                             let mut active_window = if grid_window.is_some() {
@@ -151,28 +247,124 @@ fn main() {
                             let mut max_rect = grid.get_max_area();
                             max_rect.adjust_for_border(active_window.transparent_border());
 
-                            if let Some((_, previous_rect)) = grid.previous_resize {
-                                if active_rect == max_rect {
-                                    active_window.set_pos(previous_rect, None);
-                                } else {
-                                    active_window.set_pos(max_rect, None);
+                            let mut history = HISTORY.lock().unwrap();
+
+                            // Toggle back to where the window was before it got maximized
+                            let target = match history.last_move(active_window) {
+                                Some(last_move)
+                                    if active_rect == max_rect && last_move.to == max_rect =>
+                                {
+                                    last_move.from
                                 }
-                            } else {
-                                active_window.set_pos(max_rect, None);
+                                _ => max_rect,
+                            };
+
+                            active_window.set_pos(target, None);
+
+                            history.record(active_window, active_rect, target);
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::Swap(direction) => {
+                            swap_window(
+                                get_foreground_window(),
+                                direction,
+                                &CONFIG.lock().unwrap(),
+                            );
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::RotateForward => {
+                            rotate_windows(get_foreground_window(), true, &CONFIG.lock().unwrap());
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::RotateBackward => {
+                            rotate_windows(get_foreground_window(), false, &CONFIG.lock().unwrap());
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::Undo => {
+                            for last_move in HISTORY.lock().unwrap().undo() {
+                                let mut window = last_move.window;
+
+                                window.restore();
+                                window.set_pos(last_move.from, None);
+                            }
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::Redo => {
+                            for undone_move in HISTORY.lock().unwrap().redo() {
+                                let mut window = undone_move.window;
+
+                                window.restore();
+                                window.set_pos(undone_move.to, None);
                             }
 
-                            grid.previous_resize = Some((active_window, active_rect));
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::StackNext | HotkeyType::StackPrevious => {
+                            let forward = hotkey_type == HotkeyType::StackNext;
+                            let next = STACKS
+                                .lock()
+                                .unwrap()
+                                .cycle(get_foreground_window(), forward);
 
-                        } else if preview_window.is_some() && grid_window.is_some() {
-                            let _ = sender.send(Message::CloseWindows);
-                        } else {
-                            let _ = sender.send(Message::InitializeWindows);
+                            if let Some(window) = next {
+                                window.activate();
 
-                            if hotkey_type == HotkeyType::QuickResize {
-                                GRID.lock().unwrap().quick_resize = true;
+                                tab_strips.refresh();
                             }
                         }
-                    }
+                        HotkeyType::AutoTile => auto_tile.toggle(&CONFIG.lock().unwrap()),
+                        HotkeyType::AutoTileCycle => auto_tile.cycle_layout(&CONFIG.lock().unwrap()),
+                        HotkeyType::MasterGrow => {
+                            auto_tile.adjust_master_ratio(true, &CONFIG.lock().unwrap())
+                        }
+                        HotkeyType::MasterShrink => {
+                            auto_tile.adjust_master_ratio(false, &CONFIG.lock().unwrap())
+                        }
+                        HotkeyType::SnapshotSave => {
+                            let _ = sender.send(Message::SaveSnapshot(DEFAULT_SNAPSHOT.to_owned()));
+                        }
+                        HotkeyType::SnapshotRestore => {
+                            let _ =
+                                sender.send(Message::RestoreSnapshot(DEFAULT_SNAPSHOT.to_owned()));
+                        }
+                        HotkeyType::Move(direction) => {
+                            move_window(
+                                get_foreground_window(),
+                                direction,
+                                &CONFIG.lock().unwrap(),
+                            );
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::Zone(hotkey) => {
+                            zone_hotkeys.send(
+                                get_foreground_window(),
+                                &hotkey,
+                                &CONFIG.lock().unwrap(),
+                            );
+
+                            tab_strips.refresh();
+                        }
+                        HotkeyType::Profile(profile) => {
+                            let _ = sender.send(Message::ProfileChange(profile));
+                        }
+                        HotkeyType::Main | HotkeyType::QuickResize => {
+                            if preview_window.is_some() && grid_window.is_some() {
+                                let _ = sender.send(Message::CloseWindows);
+                            } else {
+                                let _ = sender.send(Message::InitializeWindows);
+
+                                if hotkey_type == HotkeyType::QuickResize {
+                                    GRID.lock().unwrap().quick_resize = true;
+                                }
+                            }
+                        }
+                    },
                     Message::TrackMouse(window) => unsafe {
                         if !track_mouse {
                             let mut event_track: TRACKMOUSEEVENT = mem::zeroed();
@@ -185,6 +377,59 @@ fn main() {
                             track_mouse = true;
                         }
                     }
+                    Message::WindowsChanged => {
+                        if auto_tile.enabled {
+                            auto_tile.refresh(&CONFIG.lock().unwrap());
+                        }
+
+                        rule_placer.forget_closed_windows();
+                        HISTORY.lock().unwrap().retain(|window| window.exists());
+                        tab_strips.refresh();
+
+                        if grid_window.is_some() {
+                            let mut grid = GRID.lock().unwrap();
+
+                            if grid.refresh_window_list() {
+                                grid.reposition();
+                                grid.redraw();
+                            }
+                        }
+                    }
+                    Message::WindowShown(window) => {
+                        rule_placer.window_shown(window, &CONFIG.lock().unwrap());
+                    }
+                    Message::WindowFocused(window) => {
+                        rule_placer.window_focused(window, &CONFIG.lock().unwrap());
+
+                        let shown = STACKS.lock().unwrap().show(window);
+                        if shown.is_some() {
+                            tab_strips.refresh();
+                        }
+                    }
+                    Message::WindowMoved | Message::StacksChanged => {
+                        tab_strips.refresh();
+                    }
+                    Message::TabStrip(id, strip) => {
+                        tab_strips.opened(id, strip);
+                    }
+                    Message::GridChanged => {
+                        let moves = GRID.lock().unwrap().follow_grid(&mut STACKS.lock().unwrap());
+
+                        follow_grid(&moves);
+                        tab_strips.refresh();
+                    }
+                    Message::SaveSnapshot(name) => {
+                        if let Err(e) = capture_snapshot().save(&name) {
+                            show_msg_box(&format!("Error saving layout snapshot {}.\n\nErr: {}", name, e));
+                        }
+                    }
+                    Message::RestoreSnapshot(name) => match Snapshot::load(&name) {
+                        Ok(snapshot) => restore_snapshot(&snapshot),
+                        Err(e) => show_msg_box(&format!(
+                            "Error loading layout snapshot {}.\n\nErr: {}",
+                            name, e
+                        )),
+                    },
                     Message::MouseLeft => {
                         track_mouse = false;
                     }
@@ -193,54 +438,79 @@ fn main() {
 
                         if grid.grid_window != Some(window) && grid.active_window != Some(window) {
                             grid.active_window = Some(window);
+                            grid.redraw();
                         }
                     }
                     Message::MonitorChange => {
                         let mut grid = GRID.lock().unwrap();
 
                         let active_window = grid.active_window;
-                        let previous_resize = grid.previous_resize;
                         let quick_resize = grid.quick_resize;
 
                         *grid = Grid::from(&*CONFIG.lock().unwrap());
 
                         grid.grid_window = grid_window;
                         grid.active_window = active_window;
-                        grid.previous_resize = previous_resize;
                         grid.quick_resize = quick_resize;
 
+                        grid.refresh_window_list();
                         grid.reposition();
                     }
                     Message::ProfileChange(profile) => {
                         {
                             let mut active_profile = ACTIVE_PROFILE.lock().unwrap();
-                            *active_profile = profile.to_owned();
+                            *active_profile = profile;
                         }
 
-                        let mut grid = GRID.lock().unwrap();
+                        rebuild_grid(grid_window, preview_window, &mut tab_strips);
+                    }
+                    Message::ConfigChanged => match config::load_config() {
+                        Ok(config) => {
+                            let previous =
+                                mem::replace(&mut *CONFIG.lock().unwrap(), config.clone());
+
+                            if hotkeys_changed(&previous, &config) {
+                                stop_hotkey_threads(hotkey_threads);
+                                hotkey_threads = spawn_hotkey_threads(&config);
+                            }
 
-                        let active_window = grid.active_window;
-                        let previous_resize = grid.previous_resize;
-                        let quick_resize = grid.quick_resize;
+                            if previous.auto_start != config.auto_start {
+                                let toggled = unsafe {
+                                    autostart::toggle_autostart_registry_key(config.auto_start)
+                                };
 
-                        *grid = Grid::from(&*CONFIG.lock().unwrap());
+                                if let Err(e) = toggled {
+                                    show_msg_box(&format!(
+                                        "Error updating registry after reloading config.\n\nErr: {}",
+                                        e
+                                    ));
+                                }
+                            }
 
-                        grid.grid_window = grid_window;
-                        grid.active_window = active_window;
-                        grid.previous_resize = previous_resize;
-                        grid.quick_resize = quick_resize;
+                            if previous.auto_tile != config.auto_tile
+                                || previous.auto_tile_layout != config.auto_tile_layout
+                                || previous.master_ratio != config.master_ratio
+                            {
+                                auto_tile = AutoTile::from(&config);
+                                auto_tile.apply(&config);
+                            }
 
-                        grid.reposition();
-                    }
+                            rule_placer.set_rules(&config);
+
+                            rebuild_grid(grid_window, preview_window, &mut tab_strips);
+                        }
+                        // Keep running with the previous config until the file is fixed
+                        Err(e) => notify("Config not reloaded", &e.to_string()),
+                    },
                     Message::InitializeWindows => {
                         let mut grid = GRID.lock().unwrap();
                         let quick_resize = grid.quick_resize;
-                        let previous_resize = grid.previous_resize;
 
                         *grid = Grid::from(&*CONFIG.lock().unwrap());
 
                         grid.quick_resize = quick_resize;
-                        grid.previous_resize = previous_resize;
+
+                        grid.refresh_window_list();
 
                         spawn_grid_window(close_channel.1.clone());
                     }
@@ -256,8 +526,13 @@ fn main() {
 
                         grid.reset();
                         track_mouse = false;
+
+                        HISTORY.lock().unwrap().end_group();
                     }
                     Message::Exit => {
+                        GRID_CACHE.flush();
+                        tab_strips.close_all();
+
                         break;
                     }
                 }
@@ -265,3 +540,104 @@ fn main() {
         }
     }
 }
+
+/// Rebuilds the grid from the config & active profile, applying new spacing, layout
+/// and theme to the grid if it's open and to the stacks
+#[cfg(windows)]
+fn rebuild_grid(
+    grid_window: Option<Window>,
+    preview_window: Option<Window>,
+    tab_strips: &mut TabStrips,
+) {
+    let mut grid = GRID.lock().unwrap();
+
+    let active_window = grid.active_window;
+    let quick_resize = grid.quick_resize;
+
+    *grid = Grid::from(&*CONFIG.lock().unwrap());
+
+    grid.grid_window = grid_window;
+    grid.active_window = active_window;
+    grid.quick_resize = quick_resize;
+
+    // Profile hotkeys & config changes also rebuild the grid while it's closed
+    if grid_window.is_some() {
+        grid.refresh_window_list();
+        grid.reposition();
+        grid.redraw();
+    }
+
+    if let Some(preview_window) = preview_window {
+        set_preview_color(preview_window, grid.theme.preview);
+    }
+
+    let moves = grid.follow_grid(&mut STACKS.lock().unwrap());
+
+    follow_grid(&moves);
+    tab_strips.refresh();
+}
+
+/// Moves stacked windows to the new area of their zone after the grid changed
+#[cfg(windows)]
+fn follow_grid(moves: &[(Window, Rect)]) {
+    for (mut window, mut rect) in moves.iter().copied() {
+        window.restore();
+
+        rect.adjust_for_border(window.transparent_border());
+
+        window.set_pos(rect, None);
+    }
+}
+
+/// Path given to `--check-config`, if the program was started with it
+fn check_config_arg() -> Option<Option<String>> {
+    let mut args = env::args().skip(1);
+
+    match args.next() {
+        Some(arg) if arg == "--check-config" => Some(args.next()),
+        _ => None,
+    }
+}
+
+/// Prints every problem in the config file at `path`, or the one grout loads if no path
+/// is given. Exits with 1 if there are problems, or 2 if the file couldn't be read.
+fn run_check_config(path: Option<String>) -> ! {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match config::config_path() {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        },
+    };
+
+    match check_config(&path) {
+        Ok(problems) if problems.is_empty() => {
+            println!("{}: config is valid", path.display());
+            process::exit(0);
+        }
+        Ok(problems) => {
+            for problem in problems.iter() {
+                println!("{}", problem.report(&path));
+            }
+
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn main() {
+    if let Some(path) = check_config_arg() {
+        run_check_config(path);
+    }
+
+    eprintln!("grout only runs on Windows, the grout library can be used on other platforms");
+    std::process::exit(1);
+}
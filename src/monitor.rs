@@ -0,0 +1,137 @@
+use std::mem;
+use std::ptr;
+
+use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+use winapi::shared::windef::{HDC, HMONITOR, LPRECT, POINT};
+use winapi::shared::winerror::S_OK;
+use winapi::um::shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use winapi::um::wingdi::DISPLAY_DEVICEW;
+use winapi::um::winuser::{
+    EnumDisplayDevicesW, EnumDisplayMonitors, GetCursorPos, GetMonitorInfoW, MonitorFromPoint,
+    EDD_GET_DEVICE_INTERFACE_NAME, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+};
+
+use grout::dpi::BASE_DPI;
+use grout::monitor_id::MonitorDescriptor;
+use grout::Rect;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Monitor(pub HMONITOR);
+
+unsafe impl Send for Monitor {}
+
+impl Monitor {
+    unsafe fn info(self) -> MONITORINFOEXW {
+        let mut info: MONITORINFOEXW = mem::zeroed();
+        info.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+
+        GetMonitorInfoW(self.0, &mut info as *mut MONITORINFOEXW as *mut _);
+
+        info
+    }
+
+    pub fn work_area(self) -> Rect {
+        unsafe { self.info().rcWork.into() }
+    }
+
+    /// Area of the whole monitor, including the taskbar
+    pub fn bounds(self) -> Rect {
+        unsafe { self.info().rcMonitor.into() }
+    }
+
+    /// Device name, such as `\\.\DISPLAY1`
+    pub fn name(self) -> String {
+        unsafe { from_wide(&self.info().szDevice) }
+    }
+
+    /// Identifies the physical monitor, even after it got a different device name
+    pub fn descriptor(self) -> MonitorDescriptor {
+        unsafe {
+            let info = self.info();
+            let bounds = self.bounds();
+
+            let mut device: DISPLAY_DEVICEW = mem::zeroed();
+            device.cb = mem::size_of::<DISPLAY_DEVICEW>() as u32;
+
+            let device_path = if EnumDisplayDevicesW(
+                info.szDevice.as_ptr(),
+                0,
+                &mut device,
+                EDD_GET_DEVICE_INTERFACE_NAME,
+            ) != 0
+            {
+                from_wide(&device.DeviceID)
+            } else {
+                String::new()
+            };
+
+            MonitorDescriptor {
+                device_name: from_wide(&info.szDevice),
+                device_path,
+                width: bounds.width,
+                height: bounds.height,
+            }
+        }
+    }
+
+    pub fn dpi(self) -> u32 {
+        let mut dpi_x = 0;
+        let mut dpi_y = 0;
+
+        if unsafe { GetDpiForMonitor(self.0, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) } == S_OK {
+            dpi_x
+        } else {
+            BASE_DPI
+        }
+    }
+}
+
+impl PartialEq for Monitor {
+    fn eq(&self, other: &Monitor) -> bool {
+        self.0 == other.0
+    }
+}
+
+/// Null terminated wide string buffer to String
+fn from_wide(wide: &[u16]) -> String {
+    let len = wide.iter().position(|c| *c == 0).unwrap_or(wide.len());
+
+    String::from_utf16_lossy(&wide[..len])
+}
+
+/// Monitor the cursor is currently on
+pub fn get_active_monitor() -> Monitor {
+    unsafe {
+        let mut cursor_pos: POINT = mem::zeroed();
+        GetCursorPos(&mut cursor_pos);
+
+        Monitor(MonitorFromPoint(cursor_pos, MONITOR_DEFAULTTONEAREST))
+    }
+}
+
+pub fn get_monitors() -> Vec<Monitor> {
+    let mut monitors: Vec<Monitor> = vec![];
+
+    unsafe {
+        EnumDisplayMonitors(
+            ptr::null_mut(),
+            ptr::null(),
+            Some(enum_monitors_callback),
+            &mut monitors as *mut Vec<Monitor> as LPARAM,
+        );
+    }
+
+    monitors
+}
+
+unsafe extern "system" fn enum_monitors_callback(
+    monitor: HMONITOR,
+    _hdc: HDC,
+    _rect: LPRECT,
+    data: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(data as *mut Vec<Monitor>);
+    monitors.push(Monitor(monitor));
+
+    TRUE
+}
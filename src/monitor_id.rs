@@ -0,0 +1,165 @@
+//! Identifying monitors independently of the order Windows enumerates them in.
+
+use std::collections::HashMap;
+
+/// What a monitor is identified by, independent of the order Windows enumerates
+/// monitors in
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorDescriptor {
+    /// GDI device name, such as `\\.\DISPLAY1`. Gets reassigned whenever monitors are
+    /// reconnected, e.g. when docking
+    pub device_name: String,
+    /// Device interface path, such as `\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352#{...}`.
+    /// Contains the EDID manufacturer & product code and the device instance.
+    pub device_path: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl MonitorDescriptor {
+    /// Stable identifier per-monitor settings are keyed on. Falls back to the device
+    /// name if the device path couldn't be read.
+    pub fn id(&self) -> String {
+        let device = if self.device_path.is_empty() {
+            &self.device_name
+        } else {
+            &self.device_path
+        };
+
+        format!("{}@{}x{}", device, self.width, self.height)
+    }
+
+    /// Monitor goes by `name` in the config: its stable id, device path, or device
+    /// name with or without the `\\.\` prefix, such as `DISPLAY2`
+    pub fn matches(&self, name: &str) -> bool {
+        self.rank(name).is_some()
+    }
+
+    /// Entry of `settings` for the monitor, when the monitor goes by several of its
+    /// keys. The stable id wins over the device path, which wins over the device name,
+    /// so the entry doesn't depend on the order of the map.
+    pub fn lookup<'a, T>(&self, settings: &'a HashMap<String, T>) -> Option<&'a T> {
+        settings
+            .iter()
+            .filter_map(|(name, value)| Some(((self.rank(name)?, name), value)))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, value)| value)
+    }
+
+    /// How closely `name` identifies the monitor, lowest first
+    fn rank(&self, name: &str) -> Option<u8> {
+        let short_name = self.device_name.trim_start_matches(r"\\.\");
+
+        if name == self.id() {
+            Some(0)
+        } else if !self.device_path.is_empty() && name.eq_ignore_ascii_case(&self.device_path) {
+            Some(1)
+        } else if name.eq_ignore_ascii_case(&self.device_name) {
+            Some(2)
+        } else if name.eq_ignore_ascii_case(short_name) {
+            Some(3)
+        } else {
+            None
+        }
+    }
+}
+
+/// Device names like `\\.\DISPLAY1` were used as monitor keys before stable ids
+pub fn is_legacy_id(monitor: &str) -> bool {
+    monitor.starts_with(r"\\.\") && !monitor.contains('@')
+}
+
+/// Stable id of the connected monitor currently going by the legacy device name
+pub fn migrate_id(legacy: &str, monitors: &[MonitorDescriptor]) -> Option<String> {
+    monitors
+        .iter()
+        .find(|monitor| monitor.device_name == legacy)
+        .map(MonitorDescriptor::id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docked() -> MonitorDescriptor {
+        MonitorDescriptor {
+            device_name: r"\\.\DISPLAY3".to_owned(),
+            device_path: r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352".to_owned(),
+            width: 2560,
+            height: 1440,
+        }
+    }
+
+    fn laptop() -> MonitorDescriptor {
+        MonitorDescriptor {
+            device_name: r"\\.\DISPLAY1".to_owned(),
+            device_path: String::new(),
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    #[test]
+    fn id_prefers_device_path() {
+        assert_eq!(
+            docked().id(),
+            r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352@2560x1440"
+        );
+        assert_eq!(laptop().id(), r"\\.\DISPLAY1@1920x1080");
+    }
+
+    #[test]
+    fn matches_any_name() {
+        let monitor = docked();
+
+        assert!(monitor.matches(&monitor.id()));
+        assert!(monitor.matches(r"\\?\display#DEL40F0#5&2a8d6e3&0&UID4352"));
+        assert!(monitor.matches(r"\\.\DISPLAY3"));
+        assert!(monitor.matches("display3"));
+        assert!(!monitor.matches("DISPLAY1"));
+        assert!(!monitor.matches(""));
+        assert!(!laptop().matches(""));
+    }
+
+    #[test]
+    fn lookup_prefers_stable_id() {
+        let monitor = docked();
+        let mut settings = HashMap::new();
+
+        settings.insert("DISPLAY3".to_owned(), "short name");
+        assert_eq!(monitor.lookup(&settings), Some(&"short name"));
+
+        settings.insert(r"\\.\DISPLAY3".to_owned(), "device name");
+        assert_eq!(monitor.lookup(&settings), Some(&"device name"));
+
+        settings.insert(monitor.device_path.clone(), "device path");
+        assert_eq!(monitor.lookup(&settings), Some(&"device path"));
+
+        settings.insert(monitor.id(), "id");
+        settings.insert("DISPLAY1".to_owned(), "other monitor");
+        assert_eq!(monitor.lookup(&settings), Some(&"id"));
+
+        assert_eq!(laptop().lookup(&settings), Some(&"other monitor"));
+        assert_eq!(
+            MonitorDescriptor {
+                device_name: "X".to_owned(),
+                ..laptop()
+            }
+            .lookup(&settings),
+            None
+        );
+    }
+
+    #[test]
+    fn migrates_device_names() {
+        let monitors = vec![laptop(), docked()];
+
+        assert!(is_legacy_id(r"\\.\DISPLAY3"));
+        assert!(!is_legacy_id(&laptop().id()));
+        assert!(!is_legacy_id(&docked().id()));
+
+        assert_eq!(migrate_id(r"\\.\DISPLAY3", &monitors), Some(docked().id()));
+        assert_eq!(migrate_id(r"\\.\DISPLAY1", &monitors), Some(laptop().id()));
+        assert_eq!(migrate_id(r"\\.\DISPLAY2", &monitors), None);
+    }
+}
@@ -0,0 +1,403 @@
+use grout::config::Config;
+use grout::geometry::{
+    adjacent_area, cycle_width, enter_span, neighbour_span, step_span, Direction, GridGeometry,
+    Span,
+};
+use grout::rules::{parse_span, RuleSet, RuleZone, WindowProps};
+use grout::Rect;
+
+use crate::grid::monitor_grid_weights;
+use crate::monitor::{get_monitors, Monitor};
+use crate::window::{get_visible_windows, Window};
+use crate::{ACTIVE_PROFILE, HISTORY};
+
+/// Pixels a window's edges may be off from a tile's edges while still counting as
+/// filling that tile
+const SNAP_TOLERANCE: i32 = 4;
+
+/// Places new windows according to the rules in the config
+pub struct RulePlacer {
+    rules: RuleSet,
+    handled: Vec<Window>,
+}
+
+impl From<&Config> for RulePlacer {
+    fn from(config: &Config) -> Self {
+        RulePlacer {
+            rules: RuleSet::new(&config.rules).unwrap_or_default(),
+            handled: vec![],
+        }
+    }
+}
+
+impl RulePlacer {
+    /// Replaces the rules after the config was reloaded. Windows that were already
+    /// handled aren't placed again.
+    pub fn set_rules(&mut self, config: &Config) {
+        self.rules = RuleSet::new(&config.rules).unwrap_or_default();
+    }
+
+    /// Windows can still be missing their title when first shown, so they're only
+    /// marked as handled once a rule matched
+    pub fn window_shown(&mut self, window: Window, config: &Config) {
+        if !self.handled.contains(&window) && self.place(window, config) {
+            self.handled.push(window);
+        }
+    }
+
+    /// Rules are only applied the first time a window gets focus, so they don't fight
+    /// with windows moved by hand afterwards
+    pub fn window_focused(&mut self, window: Window, config: &Config) {
+        if !self.handled.contains(&window) {
+            self.handled.push(window);
+            self.place(window, config);
+        }
+    }
+
+    pub fn forget_closed_windows(&mut self) {
+        self.handled.retain(|window| window.exists());
+    }
+
+    fn place(&self, mut window: Window, config: &Config) -> bool {
+        if !window.is_app_window() {
+            return false;
+        }
+
+        let exe = window.exe_name();
+        let class = window.class_name();
+        let title = window.title();
+
+        let props = WindowProps {
+            exe: &exe,
+            class: &class,
+            title: &title,
+        };
+
+        let rule = match self.rules.find(&props) {
+            Some(rule) => rule,
+            None => return false,
+        };
+
+        let monitor = rule
+            .monitor
+            .and_then(|number| get_monitors().get(number.wrapping_sub(1)).copied())
+            .unwrap_or_else(|| window.monitor());
+
+        let profile = rule
+            .profile
+            .clone()
+            .unwrap_or_else(|| ACTIVE_PROFILE.lock().unwrap().clone());
+
+        let mut rect = match zone_area(&rule.zone, &profile, monitor, config) {
+            Some(rect) => rect,
+            None => return false,
+        };
+
+        window.restore();
+
+        rect.adjust_for_border(window.transparent_border());
+
+        window.set_pos(rect, None);
+
+        true
+    }
+}
+
+/// Sends windows to the zones of the zone hotkeys in the config
+#[derive(Default)]
+pub struct ZoneHotkeys {
+    /// Hotkey last pressed, the window it sent & where that window ended up, along
+    /// with the step of the width cycle
+    last: Option<(String, Window, Rect, usize)>,
+}
+
+impl ZoneHotkeys {
+    /// Moves the window to the zone of `hotkey` on its monitor. Pressing the same
+    /// hotkey again while the window is still in that zone cycles the zone's width.
+    pub fn send(&mut self, mut window: Window, hotkey: &str, config: &Config) {
+        if !window.is_app_window() {
+            return;
+        }
+
+        let zone = match config.zone_hotkeys.get(hotkey) {
+            Some(zone) => zone,
+            None => return,
+        };
+
+        let monitor = window.monitor();
+        let active_rect = window.rect();
+
+        let step = match &self.last {
+            Some((last_hotkey, last_window, last_rect, step))
+                if last_hotkey == hotkey && *last_window == window && *last_rect == active_rect =>
+            {
+                step + 1
+            }
+            _ => 0,
+        };
+
+        let mut rect = match zone {
+            RuleZone::Grid {
+                rows,
+                columns,
+                span,
+            } => {
+                let span = match parse_span(span) {
+                    Ok(span) => span,
+                    Err(_) => return,
+                };
+
+                let (span, columns) = cycle_width(span, *columns, step);
+
+                span_area(span, *rows, columns, monitor, config)
+            }
+            RuleZone::Named { .. } => {
+                let profile = ACTIVE_PROFILE.lock().unwrap().clone();
+
+                match zone_area(zone, &profile, monitor, config) {
+                    Some(rect) => rect,
+                    None => return,
+                }
+            }
+        };
+
+        window.restore();
+
+        rect.adjust_for_border(window.transparent_border());
+
+        window.set_pos(rect, None);
+
+        HISTORY.lock().unwrap().record(window, active_rect, rect);
+
+        self.last = Some((hotkey.to_owned(), window, window.rect(), step));
+    }
+}
+
+/// Moves the window one tile in `direction`, keeping its span. Windows at the edge
+/// of the grid continue onto the grid of the adjacent monitor.
+pub fn move_window(mut window: Window, direction: Direction, config: &Config) {
+    if !window.is_app_window() {
+        return;
+    }
+
+    let monitor = window.monitor();
+
+    let mut rect = window.rect();
+    rect.remove_border(window.transparent_border());
+
+    let (row_weights, column_weights) = monitor_grid_weights(&monitor.descriptor(), config);
+    let geometry = monitor_geometry(monitor, config, &row_weights, &column_weights);
+
+    let span = geometry.locate(rect);
+
+    let mut rect = match step_span(span, direction, row_weights.len(), column_weights.len()) {
+        Some((from_tile, to_tile)) => geometry.span(from_tile, to_tile),
+        None => {
+            let monitors = get_monitors();
+            let work_areas = monitors
+                .iter()
+                .map(|monitor| monitor.work_area())
+                .collect::<Vec<_>>();
+
+            let next_monitor = match adjacent_area(&work_areas, monitor.work_area(), direction) {
+                Some(idx) => monitors[idx],
+                None => return,
+            };
+
+            let (row_weights, column_weights) =
+                monitor_grid_weights(&next_monitor.descriptor(), config);
+            let geometry = monitor_geometry(next_monitor, config, &row_weights, &column_weights);
+
+            let (from_tile, to_tile) =
+                enter_span(span, direction, row_weights.len(), column_weights.len());
+
+            geometry.span(from_tile, to_tile)
+        }
+    };
+
+    let active_rect = window.rect();
+
+    window.restore();
+
+    rect.adjust_for_border(window.transparent_border());
+
+    window.set_pos(rect, None);
+
+    HISTORY.lock().unwrap().record(window, active_rect, rect);
+}
+
+/// Swaps the window with the tiled window next to it in `direction`
+pub fn swap_window(window: Window, direction: Direction, config: &Config) {
+    if !window.is_app_window() {
+        return;
+    }
+
+    let monitor = window.monitor();
+
+    let (row_weights, column_weights) = monitor_grid_weights(&monitor.descriptor(), config);
+    let geometry = monitor_geometry(monitor, config, &row_weights, &column_weights);
+
+    let tiled = tiled_windows(monitor, &geometry);
+    let spans = tiled.iter().map(|(_, span)| *span).collect::<Vec<_>>();
+
+    let from = match tiled
+        .iter()
+        .position(|(tiled_window, _)| *tiled_window == window)
+    {
+        Some(idx) => idx,
+        None => return,
+    };
+
+    let to = match neighbour_span(&spans, from, direction) {
+        Some(idx) => idx,
+        None => return,
+    };
+
+    move_to_spans(
+        &[(tiled[from].0, spans[to]), (tiled[to].0, spans[from])],
+        &geometry,
+    );
+}
+
+/// Moves every tiled window sharing a row of tiles with the window one position
+/// to the right, or left if not `forward`. The last window wraps around to the first
+/// position.
+pub fn rotate_windows(window: Window, forward: bool, config: &Config) {
+    if !window.is_app_window() {
+        return;
+    }
+
+    let monitor = window.monitor();
+
+    let (row_weights, column_weights) = monitor_grid_weights(&monitor.descriptor(), config);
+    let geometry = monitor_geometry(monitor, config, &row_weights, &column_weights);
+
+    let tiled = tiled_windows(monitor, &geometry);
+
+    let ((top, _), (bottom, _)) = match tiled
+        .iter()
+        .find(|(tiled_window, _)| *tiled_window == window)
+    {
+        Some((_, span)) => *span,
+        None => return,
+    };
+
+    let mut row = tiled
+        .into_iter()
+        .filter(|(_, ((other_top, _), (other_bottom, _)))| {
+            *other_top <= bottom && top <= *other_bottom
+        })
+        .collect::<Vec<_>>();
+
+    if row.len() < 2 {
+        return;
+    }
+
+    row.sort_by_key(|(_, ((top, left), _))| (*left, *top));
+
+    let count = row.len();
+    let moves = (0..count)
+        .map(|idx| {
+            let target = if forward {
+                (idx + 1) % count
+            } else {
+                (idx + count - 1) % count
+            };
+
+            (row[idx].0, row[target].1)
+        })
+        .collect::<Vec<_>>();
+
+    move_to_spans(&moves, &geometry);
+}
+
+/// Windows on the monitor that fill a span of tiles, along with that span
+fn tiled_windows(monitor: Monitor, geometry: &GridGeometry) -> Vec<(Window, Span)> {
+    get_visible_windows()
+        .into_iter()
+        .filter(|window| {
+            !window.is_minimized() && !window.is_maximized() && window.monitor() == monitor
+        })
+        .filter_map(|window| {
+            let mut rect = window.rect();
+            rect.remove_border(window.transparent_border());
+
+            Some((window, geometry.snapped(rect, SNAP_TOLERANCE)?))
+        })
+        .collect()
+}
+
+/// Moves each window to its span, adjusting for that window's own border
+fn move_to_spans(moves: &[(Window, Span)], geometry: &GridGeometry) {
+    let mut history = HISTORY.lock().unwrap();
+
+    for (mut window, (from_tile, to_tile)) in moves.iter().copied() {
+        let active_rect = window.rect();
+        let mut rect = geometry.span(from_tile, to_tile);
+
+        window.restore();
+
+        rect.adjust_for_border(window.transparent_border());
+
+        window.set_pos(rect, None);
+
+        history.record(window, active_rect, rect);
+    }
+}
+
+/// Grid laid out over the monitor's work area, with padding & margins at its DPI
+fn monitor_geometry<'a>(
+    monitor: Monitor,
+    config: &Config,
+    row_weights: &'a [u32],
+    column_weights: &'a [u32],
+) -> GridGeometry<'a> {
+    let monitor_dpi = monitor.dpi();
+    let work_area = monitor.work_area();
+    let spacing = config.spacing(&ACTIVE_PROFILE.lock().unwrap(), &monitor.descriptor());
+
+    GridGeometry {
+        area: work_area,
+        padding: spacing.padding(work_area, monitor_dpi),
+        margin: spacing.margin(work_area, monitor_dpi),
+        row_weights,
+        column_weights,
+    }
+}
+
+/// Area of the zone on the monitor. Named zones are looked up in the layout of `profile`.
+fn zone_area(zone: &RuleZone, profile: &str, monitor: Monitor, config: &Config) -> Option<Rect> {
+    match zone {
+        RuleZone::Grid {
+            rows,
+            columns,
+            span,
+        } => {
+            let span = parse_span(span).ok()?;
+
+            Some(span_area(span, *rows, *columns, monitor, config))
+        }
+        RuleZone::Named { name } => config
+            .layout_areas(
+                profile,
+                &monitor.descriptor(),
+                monitor.work_area(),
+                monitor.dpi(),
+            )?
+            .into_iter()
+            .find(|(zone, _)| zone == name)
+            .map(|(_, area)| area),
+    }
+}
+
+/// Area of the span in an evenly divided grid of `rows` x `columns` on the monitor
+fn span_area(span: Span, rows: usize, columns: usize, monitor: Monitor, config: &Config) -> Rect {
+    let row_weights = vec![1; rows];
+    let column_weights = vec![1; columns];
+
+    let geometry = monitor_geometry(monitor, config, &row_weights, &column_weights);
+
+    let (from_tile, to_tile) = span;
+
+    geometry.span(from_tile, to_tile)
+}
@@ -0,0 +1,87 @@
+//! Rectangles in screen coordinates.
+
+use std::fmt::{Display, Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+use winapi::shared::windef::RECT;
+
+/// x & y coordinates are relative to top left of screen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    /// Point lies within the rect, edges included
+    pub fn contains_point(self, point: (i32, i32)) -> bool {
+        point.0 >= self.x
+            && point.0 <= self.x + self.width
+            && point.1 >= self.y
+            && point.1 <= self.y + self.height
+    }
+
+    /// Empty rect at the origin
+    pub fn zero() -> Self {
+        Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    /// Grows a window's visible area by its invisible resize border, giving the rect to
+    /// position the window with
+    pub fn adjust_for_border(&mut self, border: (i32, i32)) {
+        self.x -= border.0;
+        self.width += border.0 * 2;
+        self.height += border.1;
+    }
+
+    /// Reverses `adjust_for_border`, giving the visible area of a window
+    pub fn remove_border(&mut self, border: (i32, i32)) {
+        self.x += border.0;
+        self.width -= border.0 * 2;
+        self.height -= border.1;
+    }
+}
+
+impl Display for Rect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(f, "x: {}", self.x)?;
+        writeln!(f, "y: {}", self.y)?;
+        writeln!(f, "width: {}", self.width)?;
+        writeln!(f, "height: {}", self.height)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl From<RECT> for Rect {
+    fn from(rect: RECT) -> Self {
+        Rect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl From<Rect> for RECT {
+    fn from(rect: Rect) -> Self {
+        RECT {
+            left: rect.x,
+            top: rect.y,
+            right: rect.x + rect.width,
+            bottom: rect.y + rect.height,
+        }
+    }
+}
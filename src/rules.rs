@@ -0,0 +1,338 @@
+//! Rules placing windows into zones by executable, class or title.
+
+use anyhow::format_err;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Places windows matching `exe`, `class` and `title` into a zone. Criteria that
+/// aren't set match any window.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Rule {
+    pub exe: Option<String>,
+    pub class: Option<String>,
+    /// Regular expression matched against the window title
+    pub title: Option<String>,
+    pub zone: RuleZone,
+    /// Profile whose layout the named zone is looked up in, defaults to the active profile
+    pub profile: Option<String>,
+    /// Monitor number, starting at 1. Defaults to the monitor the window opened on
+    pub monitor: Option<usize>,
+}
+
+/// Zone a rule places windows in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RuleZone {
+    /// Span of grid cells, given as `row,column` or `row,column:row,column`
+    Grid {
+        rows: usize,
+        columns: usize,
+        span: String,
+    },
+    /// Zone of a custom layout
+    Named { name: String },
+}
+
+impl RuleZone {
+    /// Checks a span of grid cells lies within its grid
+    pub fn validate(&self) -> Result<()> {
+        if let RuleZone::Grid {
+            rows,
+            columns,
+            span,
+        } = self
+        {
+            let (_, to) = parse_span(span)?;
+
+            if to.0 >= *rows || to.1 >= *columns {
+                return Err(format_err!(
+                    "span {} is outside of a {}x{} grid",
+                    span,
+                    rows,
+                    columns
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What a rule can be matched against
+pub struct WindowProps<'a> {
+    pub exe: &'a str,
+    pub class: &'a str,
+    pub title: &'a str,
+}
+
+/// Rules with their title patterns compiled
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<(Rule, Option<Regex>)>,
+}
+
+impl Rule {
+    /// Checks the rule can match a window & its zone lies within its grid
+    pub fn validate(&self) -> Result<()> {
+        self.title_pattern().map(|_| ())
+    }
+
+    fn title_pattern(&self) -> Result<Option<Regex>> {
+        if self.exe.is_none() && self.class.is_none() && self.title.is_none() {
+            return Err(format_err!("needs at least one of exe, class or title"));
+        }
+
+        self.zone.validate()?;
+
+        match &self.title {
+            Some(title) => Regex::new(title)
+                .map(Some)
+                .map_err(|e| format_err!("invalid title pattern. {}", e)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl RuleSet {
+    /// Compiles the title pattern of each rule. Fails on the first invalid rule.
+    pub fn new(rules: &[Rule]) -> Result<RuleSet> {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .map(|(idx, rule)| {
+                let title = rule
+                    .title_pattern()
+                    .map_err(|e| format_err!("Rule {}: {}", idx + 1, e))?;
+
+                Ok((rule.clone(), title))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RuleSet { rules })
+    }
+
+    /// First rule matching the window
+    pub fn find(&self, window: &WindowProps) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .find(|(rule, title)| {
+                let exe = rule
+                    .exe
+                    .as_ref()
+                    .map(|exe| exe.eq_ignore_ascii_case(window.exe));
+                let class = rule.class.as_ref().map(|class| class == window.class);
+                let title = title.as_ref().map(|title| title.is_match(window.title));
+
+                [exe, class, title]
+                    .iter()
+                    .all(|matched| matched.unwrap_or(true))
+            })
+            .map(|(rule, _)| rule)
+    }
+}
+
+/// Parses `row,column` or `row,column:row,column` into the top left & bottom right
+/// tiles of the span
+pub fn parse_span(span: &str) -> Result<((usize, usize), (usize, usize))> {
+    let parse_tile = |tile: &str| -> Result<(usize, usize)> {
+        let mut parts = tile.split(',').map(|part| part.trim().parse::<usize>());
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(row)), Some(Ok(column)), None) => Ok((row, column)),
+            _ => Err(format_err!(
+                "Invalid span <{}>: expected row,column or row,column:row,column",
+                span
+            )),
+        }
+    };
+
+    let mut tiles = span.split(':');
+
+    let from = parse_tile(tiles.next().unwrap_or_default())?;
+    let to = match tiles.next() {
+        Some(tile) => parse_tile(tile)?,
+        None => from,
+    };
+
+    if tiles.next().is_some() {
+        return Err(format_err!(
+            "Invalid span <{}>: expected row,column or row,column:row,column",
+            span
+        ));
+    }
+
+    Ok((
+        (from.0.min(to.0), from.1.min(to.1)),
+        (from.0.max(to.0), from.1.max(to.1)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(exe: Option<&str>, class: Option<&str>, title: Option<&str>, zone: &str) -> Rule {
+        Rule {
+            exe: exe.map(str::to_owned),
+            class: class.map(str::to_owned),
+            title: title.map(str::to_owned),
+            zone: RuleZone::Named {
+                name: zone.to_owned(),
+            },
+            profile: None,
+            monitor: None,
+        }
+    }
+
+    fn zone_for<'a>(rules: &'a RuleSet, exe: &str, class: &str, title: &str) -> Option<&'a str> {
+        rules
+            .find(&WindowProps { exe, class, title })
+            .map(|rule| match &rule.zone {
+                RuleZone::Named { name } => name.as_str(),
+                RuleZone::Grid { span, .. } => span.as_str(),
+            })
+    }
+
+    #[test]
+    fn matches_exe_ignoring_case() {
+        let rules = RuleSet::new(&[rule(Some("code.exe"), None, None, "left")]).unwrap();
+
+        assert_eq!(
+            zone_for(&rules, "Code.EXE", "Chrome_WidgetWin_1", "grout"),
+            Some("left")
+        );
+        assert_eq!(zone_for(&rules, "code.exe.bak", "", ""), None);
+    }
+
+    #[test]
+    fn matches_class_exactly() {
+        let rules = RuleSet::new(&[rule(None, Some("Notepad"), None, "right")]).unwrap();
+
+        assert_eq!(
+            zone_for(&rules, "notepad.exe", "Notepad", ""),
+            Some("right")
+        );
+        assert_eq!(zone_for(&rules, "notepad.exe", "notepad", ""), None);
+    }
+
+    #[test]
+    fn matches_title_pattern() {
+        let rules = RuleSet::new(&[rule(None, None, Some(r"^Inbox \(\d+\)"), "top")]).unwrap();
+
+        assert_eq!(
+            zone_for(&rules, "outlook.exe", "", "Inbox (12) - Mail"),
+            Some("top")
+        );
+        assert_eq!(zone_for(&rules, "outlook.exe", "", "Re: Inbox (12)"), None);
+    }
+
+    #[test]
+    fn needs_every_criteria() {
+        let rules = RuleSet::new(&[rule(
+            Some("firefox.exe"),
+            None,
+            Some("Picture-in-Picture"),
+            "corner",
+        )])
+        .unwrap();
+
+        assert_eq!(
+            zone_for(
+                &rules,
+                "firefox.exe",
+                "MozillaDialogClass",
+                "Picture-in-Picture"
+            ),
+            Some("corner")
+        );
+        assert_eq!(
+            zone_for(
+                &rules,
+                "firefox.exe",
+                "MozillaWindowClass",
+                "Mozilla Firefox"
+            ),
+            None
+        );
+        assert_eq!(
+            zone_for(&rules, "chrome.exe", "", "Picture-in-Picture"),
+            None
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = RuleSet::new(&[
+            rule(
+                Some("firefox.exe"),
+                None,
+                Some("Picture-in-Picture"),
+                "corner",
+            ),
+            rule(Some("firefox.exe"), None, None, "left"),
+            rule(None, None, Some("Firefox"), "right"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            zone_for(&rules, "firefox.exe", "", "Picture-in-Picture"),
+            Some("corner")
+        );
+        assert_eq!(
+            zone_for(&rules, "firefox.exe", "", "Mozilla Firefox"),
+            Some("left")
+        );
+        assert_eq!(
+            zone_for(&rules, "waterfox.exe", "", "Mozilla Firefox"),
+            Some("right")
+        );
+    }
+
+    #[test]
+    fn no_match() {
+        let rules = RuleSet::new(&[rule(Some("code.exe"), None, None, "left")]).unwrap();
+
+        assert_eq!(
+            zone_for(&rules, "explorer.exe", "CabinetWClass", "Downloads"),
+            None
+        );
+        assert_eq!(zone_for(&RuleSet::default(), "code.exe", "", ""), None);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let error = |rule: Rule| RuleSet::new(&[rule]).err().map(|e| e.to_string());
+
+        assert_eq!(
+            error(rule(None, None, None, "left")),
+            Some("Rule 1: needs at least one of exe, class or title".to_owned())
+        );
+        assert!(error(rule(None, None, Some("(unclosed"), "left"))
+            .unwrap()
+            .starts_with("Rule 1: invalid title pattern."));
+
+        let outside = Rule {
+            zone: RuleZone::Grid {
+                rows: 2,
+                columns: 2,
+                span: "0,0:1,2".to_owned(),
+            },
+            ..rule(Some("code.exe"), None, None, "")
+        };
+        assert_eq!(
+            error(outside),
+            Some("Rule 1: span 0,0:1,2 is outside of a 2x2 grid".to_owned())
+        );
+    }
+
+    #[test]
+    fn parses_spans() {
+        assert_eq!(parse_span("1,2").unwrap(), ((1, 2), (1, 2)));
+        assert_eq!(parse_span(" 2,3 : 0,1 ").unwrap(), ((0, 1), (2, 3)));
+        assert!(parse_span("1").is_err());
+        assert!(parse_span("1,2,3").is_err());
+        assert!(parse_span("0,0:1,1:2,2").is_err());
+    }
+}
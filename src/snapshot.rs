@@ -0,0 +1,264 @@
+//! Snapshots of the position & state of every window on the desktop.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::format_err;
+use serde::{Deserialize, Serialize};
+
+use crate::monitor_id::MonitorDescriptor;
+use crate::Rect;
+use crate::Result;
+
+/// Name used by the snapshot hotkeys and the tray's "Save Layout"
+pub const DEFAULT_SNAPSHOT: &str = "default";
+
+/// Position & state of every visible window on the desktop
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Snapshot {
+    pub windows: Vec<WindowSnapshot>,
+}
+
+/// Windows are identified by executable and title rather than handle, so snapshots
+/// can be restored after the windows were closed and reopened
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WindowSnapshot {
+    pub exe: String,
+    pub title: String,
+    /// Stable id of the monitor the window was on, see [`MonitorDescriptor::id`]
+    pub monitor: String,
+    /// Position when neither maximized nor minimized, in screen coordinates
+    pub rect: Rect,
+    pub state: WindowState,
+}
+
+impl WindowSnapshot {
+    /// Monitor the window was saved on is one of `monitors`. Snapshots saved before
+    /// stable ids name the monitor by device name.
+    pub fn on_connected_monitor(&self, monitors: &[MonitorDescriptor]) -> bool {
+        monitors
+            .iter()
+            .any(|monitor| monitor.matches(&self.monitor))
+    }
+}
+
+/// Whether a window was maximized or minimized when the snapshot was taken
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WindowState {
+    Normal,
+    Maximized,
+    Minimized,
+}
+
+impl Snapshot {
+    /// Snapshot in the format it's saved in
+    pub fn to_ron(&self) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    /// Reads a snapshot saved by `to_ron`
+    pub fn from_ron(ron: &str) -> Result<Snapshot> {
+        Ok(ron::de::from_str(ron)?)
+    }
+
+    /// Writes the snapshot to `<name>.ron` in the `snapshots` directory next to the
+    /// config, replacing any snapshot of the same name
+    pub fn save(&self, name: &str) -> Result<()> {
+        let dir = snapshot_dir()?;
+
+        if !dir.exists() {
+            fs::create_dir_all(&dir)?;
+        }
+
+        fs::write(dir.join(format!("{}.ron", name)), self.to_ron()?)?;
+
+        Ok(())
+    }
+
+    /// Reads the snapshot saved under `name`
+    pub fn load(name: &str) -> Result<Snapshot> {
+        let path = snapshot_dir()?.join(format!("{}.ron", name));
+
+        Snapshot::from_ron(&fs::read_to_string(path)?)
+    }
+
+    /// Pairs each open window, given as `(window, exe, title)`, with the snapshot
+    /// entry it should be restored to. Windows matching both executable and title
+    /// are paired first, remaining entries go to other windows of the same executable
+    /// in order.
+    pub fn match_windows<W: Copy>(&self, windows: &[(W, String, String)]) -> Vec<(W, usize)> {
+        let mut entry_used = vec![false; self.windows.len()];
+        let mut window_used = vec![false; windows.len()];
+        let mut matches = vec![];
+
+        for match_title in [true, false].iter() {
+            for (entry_idx, entry) in self.windows.iter().enumerate() {
+                if entry_used[entry_idx] {
+                    continue;
+                }
+
+                let found = windows.iter().enumerate().find(|(idx, (_, exe, title))| {
+                    !window_used[*idx]
+                        && entry.exe.eq_ignore_ascii_case(exe)
+                        && (!match_title || entry.title == *title)
+                });
+
+                if let Some((window_idx, (window, _, _))) = found {
+                    entry_used[entry_idx] = true;
+                    window_used[window_idx] = true;
+                    matches.push((*window, entry_idx));
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+/// Names of all saved snapshots
+pub fn list_snapshots() -> Vec<String> {
+    let mut names = snapshot_dir()
+        .ok()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("ron"))
+                .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    names.sort();
+    names
+}
+
+fn snapshot_dir() -> Result<PathBuf> {
+    let mut dir =
+        dirs::config_dir().ok_or_else(|| format_err!("Failed to get config directory"))?;
+    dir.push("grout");
+    dir.push("snapshots");
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(exe: &str, title: &str, x: i32, state: WindowState) -> WindowSnapshot {
+        WindowSnapshot {
+            exe: exe.to_owned(),
+            title: title.to_owned(),
+            monitor: r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352@1920x1080".to_owned(),
+            rect: Rect {
+                x,
+                y: -8,
+                width: 960,
+                height: 1040,
+            },
+            state,
+        }
+    }
+
+    fn window(hwnd: usize, exe: &str, title: &str) -> (usize, String, String) {
+        (hwnd, exe.to_owned(), title.to_owned())
+    }
+
+    fn monitor(device_name: &str, device_path: &str) -> MonitorDescriptor {
+        MonitorDescriptor {
+            device_name: device_name.to_owned(),
+            device_path: device_path.to_owned(),
+            width: 1920,
+            height: 1080,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_ron() {
+        let snapshot = Snapshot {
+            windows: vec![
+                entry("code.exe", "main.rs - grout", -1920, WindowState::Maximized),
+                entry(
+                    "firefox.exe",
+                    "\"Quoted\" title (1)",
+                    960,
+                    WindowState::Normal,
+                ),
+                entry("notepad.exe", "", 0, WindowState::Minimized),
+            ],
+        };
+
+        let ron = snapshot.to_ron().unwrap();
+
+        assert_eq!(Snapshot::from_ron(&ron).unwrap(), snapshot);
+        assert_eq!(
+            Snapshot::from_ron(&Snapshot::default().to_ron().unwrap()).unwrap(),
+            Snapshot::default()
+        );
+    }
+
+    #[test]
+    fn reads_saved_format() {
+        let ron = r#"(
+            windows: [
+                (
+                    exe: "code.exe",
+                    title: "grout",
+                    monitor: "\\\\.\\DISPLAY1",
+                    rect: (x: 0, y: 0, width: 800, height: 600),
+                    state: Maximized,
+                ),
+            ],
+        )"#;
+
+        let snapshot = Snapshot::from_ron(ron).unwrap();
+
+        assert_eq!(snapshot.windows[0].monitor, r"\\.\DISPLAY1");
+        assert_eq!(snapshot.windows[0].state, WindowState::Maximized);
+        assert!(Snapshot::from_ron("(windows: [(exe: \"code.exe\")])").is_err());
+    }
+
+    #[test]
+    fn matches_title_before_exe() {
+        let snapshot = Snapshot {
+            windows: vec![
+                entry("code.exe", "first", 0, WindowState::Normal),
+                entry("code.exe", "second", 960, WindowState::Normal),
+                entry("slack.exe", "Slack", 0, WindowState::Normal),
+            ],
+        };
+
+        let windows = vec![
+            window(1, "Code.exe", "untitled"),
+            window(2, "code.exe", "second"),
+            window(3, "notepad.exe", "first"),
+        ];
+
+        assert_eq!(snapshot.match_windows(&windows), vec![(2, 1), (1, 0)]);
+        assert_eq!(snapshot.match_windows::<usize>(&[]), vec![]);
+    }
+
+    #[test]
+    fn checks_monitor_is_connected() {
+        let saved = entry("code.exe", "grout", 0, WindowState::Normal);
+        let docked = monitor(r"\\.\DISPLAY3", r"\\?\DISPLAY#DEL40F0#5&2a8d6e3&0&UID4352");
+        let laptop = monitor(
+            r"\\.\DISPLAY1",
+            r"\\?\DISPLAY#BOE0868#4&1ce1c9b&0&UID265988",
+        );
+
+        assert!(saved.on_connected_monitor(&[laptop.clone(), docked]));
+        assert!(!saved.on_connected_monitor(std::slice::from_ref(&laptop)));
+
+        let legacy = WindowSnapshot {
+            monitor: r"\\.\DISPLAY1".to_owned(),
+            ..saved
+        };
+        assert!(legacy.on_connected_monitor(&[laptop]));
+    }
+}
@@ -0,0 +1,392 @@
+//! Margins & padding, in pixels or percentages of the work area.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use anyhow::format_err;
+use serde::{Deserialize, Serialize};
+
+use crate::dpi;
+use crate::geometry::Insets;
+use crate::{Error, Rect, Result};
+
+/// Size of a margin or padding in the config. Either pixels at 100% display scaling,
+/// such as `10` or `"10px"`, or a percentage of the work area, such as `"2.5%"`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawLength", into = "RawLength")]
+pub enum Length {
+    Pixels(u32),
+    Percent(f32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Pixels(0)
+    }
+}
+
+impl Length {
+    /// Size in physical pixels. Percentages are of `extent`, pixels are scaled to `dpi`.
+    pub fn resolve(self, extent: i32, dpi: u32) -> i32 {
+        match self {
+            Length::Pixels(pixels) => dpi::scale(pixels as i32, dpi),
+            Length::Percent(percent) => (extent as f32 * percent / 100.0).round() as i32,
+        }
+    }
+
+    pub(crate) fn validate(self) -> Result<()> {
+        match self {
+            Length::Percent(percent) if !(0.0..=100.0).contains(&percent) => {
+                Err(format_err!("{} must be between 0% and 100%", self))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Length::Pixels(pixels) => write!(f, "{}px", pixels),
+            Length::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawLength {
+    Pixels(u32),
+    Text(String),
+}
+
+impl TryFrom<RawLength> for Length {
+    type Error = Error;
+
+    fn try_from(raw: RawLength) -> Result<Self> {
+        let text = match raw {
+            RawLength::Pixels(pixels) => return Ok(Length::Pixels(pixels)),
+            RawLength::Text(text) => text,
+        };
+
+        let text = text.trim();
+
+        if let Some(percent) = text.strip_suffix('%') {
+            return percent
+                .trim()
+                .parse()
+                .map(Length::Percent)
+                .map_err(|_| format_err!("Invalid percentage {}", text));
+        }
+
+        text.trim_end_matches("px")
+            .trim()
+            .parse()
+            .map(Length::Pixels)
+            .map_err(|_| {
+                format_err!(
+                    "Invalid size {}, expected pixels such as 10 or a percentage such as 2.5%",
+                    text
+                )
+            })
+    }
+}
+
+impl From<Length> for RawLength {
+    fn from(length: Length) -> Self {
+        match length {
+            Length::Pixels(pixels) => RawLength::Pixels(pixels),
+            Length::Percent(_) => RawLength::Text(length.to_string()),
+        }
+    }
+}
+
+/// Padding between the edges of the work area and the windows. Either one size for
+/// every edge, or a size per edge where edges left out get no padding.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawPadding", into = "RawPadding")]
+pub struct Padding {
+    pub top: Length,
+    pub right: Length,
+    pub bottom: Length,
+    pub left: Length,
+}
+
+impl Padding {
+    /// Same padding along every edge
+    pub fn uniform(length: Length) -> Self {
+        Padding {
+            top: length,
+            right: length,
+            bottom: length,
+            left: length,
+        }
+    }
+
+    /// Padding in physical pixels. Percentages of the left & right edges are of the
+    /// width of `work_area`, those of the top & bottom edges of its height. Padding
+    /// wider or taller than the work area is shrunk to fit it, keeping its proportions.
+    pub fn resolve(&self, work_area: Rect, dpi: u32) -> Insets {
+        let (left, right) = fit(
+            self.left.resolve(work_area.width, dpi),
+            self.right.resolve(work_area.width, dpi),
+            work_area.width,
+        );
+        let (top, bottom) = fit(
+            self.top.resolve(work_area.height, dpi),
+            self.bottom.resolve(work_area.height, dpi),
+            work_area.height,
+        );
+
+        Insets {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        for length in [self.top, self.right, self.bottom, self.left].iter() {
+            length.validate()?;
+        }
+
+        let edges = [
+            ("left", self.left, "right", self.right),
+            ("top", self.top, "bottom", self.bottom),
+        ];
+
+        for (start_name, start, end_name, end) in edges.iter() {
+            if let (Length::Percent(start_percent), Length::Percent(end_percent)) = (start, end) {
+                if start_percent + end_percent > 100.0 {
+                    return Err(format_err!(
+                        "{} {} and {} {} add up to more than 100%",
+                        start_name,
+                        start,
+                        end_name,
+                        end
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sizes of padding on opposite edges, shrunk to add up to at most `extent`
+fn fit(start: i32, end: i32, extent: i32) -> (i32, i32) {
+    let extent = extent.max(0);
+
+    if start + end <= extent {
+        return (start, end);
+    }
+
+    let start = (start as i64 * extent as i64 / (start + end) as i64) as i32;
+
+    (start, extent - start)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawPadding {
+    Uniform(Length),
+    Edges {
+        #[serde(default)]
+        top: Length,
+        #[serde(default)]
+        right: Length,
+        #[serde(default)]
+        bottom: Length,
+        #[serde(default)]
+        left: Length,
+    },
+}
+
+impl From<RawPadding> for Padding {
+    fn from(raw: RawPadding) -> Self {
+        match raw {
+            RawPadding::Uniform(length) => Padding::uniform(length),
+            RawPadding::Edges {
+                top,
+                right,
+                bottom,
+                left,
+            } => Padding {
+                top,
+                right,
+                bottom,
+                left,
+            },
+        }
+    }
+}
+
+impl From<Padding> for RawPadding {
+    fn from(padding: Padding) -> Self {
+        if padding == Padding::uniform(padding.top) {
+            RawPadding::Uniform(padding.top)
+        } else {
+            RawPadding::Edges {
+                top: padding.top,
+                right: padding.right,
+                bottom: padding.bottom,
+                left: padding.left,
+            }
+        }
+    }
+}
+
+/// Margins & padding used on one monitor instead of the ones for every monitor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorSpacing {
+    #[serde(default)]
+    pub margins: Option<Length>,
+    #[serde(default)]
+    pub window_padding: Option<Padding>,
+}
+
+impl MonitorSpacing {
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(margins) = self.margins {
+            margins
+                .validate()
+                .map_err(|e| format_err!("margins: {}", e))?;
+        }
+
+        if let Some(padding) = self.window_padding {
+            padding
+                .validate()
+                .map_err(|e| format_err!("window_padding: {}", e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Margins & padding that apply on a monitor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spacing {
+    /// Space between neighbouring windows
+    pub margins: Length,
+    /// Space between the edges of the work area and the windows
+    pub padding: Padding,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Spacing {
+            margins: Length::Pixels(10),
+            padding: Padding::uniform(Length::Pixels(10)),
+        }
+    }
+}
+
+impl Spacing {
+    /// Padding in physical pixels, see `Padding::resolve`
+    pub fn padding(&self, work_area: Rect, dpi: u32) -> Insets {
+        self.padding.resolve(work_area, dpi)
+    }
+
+    /// Margin in physical pixels. Percentages are of the shorter side of `work_area`,
+    /// so the space between windows is the same horizontally & vertically.
+    pub fn margin(&self, work_area: Rect, dpi: u32) -> i32 {
+        self.margins
+            .resolve(work_area.width.min(work_area.height), dpi)
+    }
+
+    /// Work area left over for the windows after padding
+    pub fn padded_area(&self, work_area: Rect, dpi: u32) -> Rect {
+        self.padding(work_area, dpi).shrink(work_area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORK_AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 2000,
+        height: 1000,
+    };
+
+    #[test]
+    fn pixels_scale_per_monitor() {
+        let spacing = Spacing::default();
+
+        assert_eq!(spacing.margin(WORK_AREA, 96), 10);
+        assert_eq!(spacing.margin(WORK_AREA, 144), 15);
+        assert_eq!(spacing.margin(WORK_AREA, 192), 20);
+        assert_eq!(spacing.padding(WORK_AREA, 192), Insets::uniform(20));
+        assert_eq!(
+            spacing.padded_area(WORK_AREA, 120),
+            Rect {
+                x: 13,
+                y: 13,
+                width: 1974,
+                height: 974,
+            }
+        );
+    }
+
+    #[test]
+    fn percentages_ignore_dpi() {
+        let spacing = Spacing {
+            margins: Length::Percent(2.0),
+            padding: Padding::uniform(Length::Percent(1.0)),
+        };
+
+        for dpi in [96, 120, 192].iter() {
+            assert_eq!(spacing.margin(WORK_AREA, *dpi), 20);
+            assert_eq!(
+                spacing.padding(WORK_AREA, *dpi),
+                Insets {
+                    top: 10,
+                    right: 20,
+                    bottom: 10,
+                    left: 20,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn parses_lengths() {
+        let parse = |raw: RawLength| Length::try_from(raw).map_err(|e| e.to_string());
+
+        assert_eq!(parse(RawLength::Pixels(8)), Ok(Length::Pixels(8)));
+        assert_eq!(parse(RawLength::Text("8px".into())), Ok(Length::Pixels(8)));
+        assert_eq!(
+            parse(RawLength::Text(" 2.5% ".into())),
+            Ok(Length::Percent(2.5))
+        );
+        assert!(parse(RawLength::Text("wide".into())).is_err());
+        assert!(Length::Percent(101.0).validate().is_err());
+    }
+
+    #[test]
+    fn padding_fits_in_the_work_area() {
+        let padding = Padding {
+            top: Length::Percent(30.0),
+            right: Length::Percent(60.0),
+            bottom: Length::Pixels(800),
+            left: Length::Percent(60.0),
+        };
+
+        assert_eq!(
+            padding.validate().map_err(|e| e.to_string()),
+            Err("left 60% and right 60% add up to more than 100%".to_owned())
+        );
+        assert_eq!(
+            padding.resolve(WORK_AREA, 96),
+            Insets {
+                top: 272,
+                right: 1000,
+                bottom: 728,
+                left: 1000,
+            }
+        );
+    }
+}
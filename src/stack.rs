@@ -0,0 +1,309 @@
+//! Windows stacked in the same zone, behind a strip of tabs.
+
+use crate::geometry::Span;
+use crate::Rect;
+
+/// Windows placed in the same zone, shown one at a time behind a strip of tabs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stack<W> {
+    pub id: usize,
+    /// Stable id of the monitor the zone is on
+    pub monitor: String,
+    /// Area of the zone, before adjusting for any window's border
+    pub area: Rect,
+    /// Tiles the zone spans, if it's a zone of the grid rather than of a custom layout
+    pub span: Option<Span>,
+    pub windows: Vec<W>,
+    visible: usize,
+}
+
+impl<W: Copy + PartialEq> Stack<W> {
+    /// Window shown on top of the others
+    pub fn visible_window(&self) -> W {
+        self.windows[self.visible]
+    }
+
+    /// Only zones holding more than one window get tabs
+    pub fn is_stacked(&self) -> bool {
+        self.windows.len() > 1
+    }
+}
+
+/// Every zone windows were placed in, along with the windows in each
+#[derive(Default)]
+pub struct Stacks<W> {
+    stacks: Vec<Stack<W>>,
+    next_id: usize,
+}
+
+impl<W: Copy + PartialEq> Stacks<W> {
+    /// Puts the window on top of the stack in the zone, taking it out of any other
+    /// stack. Returns the id of the stack.
+    pub fn add(&mut self, window: W, monitor: &str, area: Rect, span: Option<Span>) -> usize {
+        self.remove(window);
+
+        if let Some(stack) = self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.monitor == monitor && stack.area == area)
+        {
+            stack.windows.push(window);
+            stack.visible = stack.windows.len() - 1;
+
+            return stack.id;
+        }
+
+        self.next_id += 1;
+
+        self.stacks.push(Stack {
+            id: self.next_id,
+            monitor: monitor.to_owned(),
+            area,
+            span,
+            windows: vec![window],
+            visible: 0,
+        });
+
+        self.next_id
+    }
+
+    /// Takes the window out of its stack
+    pub fn remove(&mut self, window: W) {
+        self.retain(|other, _| other != window);
+    }
+
+    /// Takes windows that don't satisfy `keep` out of their stack, such as closed
+    /// windows or ones moved out of the zone. `keep` is given each window along with
+    /// the area of its zone.
+    pub fn retain(&mut self, keep: impl Fn(W, Rect) -> bool) {
+        for stack in self.stacks.iter_mut() {
+            let visible = stack.visible_window();
+            let area = stack.area;
+
+            stack.windows.retain(|window| keep(*window, area));
+
+            stack.visible = stack
+                .windows
+                .iter()
+                .position(|window| *window == visible)
+                .unwrap_or(0);
+        }
+
+        self.stacks.retain(|stack| !stack.windows.is_empty());
+    }
+
+    /// Stack with the id returned by `add`
+    pub fn get(&self, id: usize) -> Option<&Stack<W>> {
+        self.stacks.iter().find(|stack| stack.id == id)
+    }
+
+    /// Every stack, including zones holding a single window
+    pub fn iter(&self) -> impl Iterator<Item = &Stack<W>> {
+        self.stacks.iter()
+    }
+
+    /// Remembers the window as the one shown in its stack, such as after it was
+    /// focused. Returns the id of its stack if that changed which window is shown.
+    pub fn show(&mut self, window: W) -> Option<usize> {
+        let stack = self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.windows.contains(&window))?;
+
+        let idx = stack.windows.iter().position(|other| *other == window)?;
+
+        if stack.visible == idx {
+            return None;
+        }
+
+        stack.visible = idx;
+
+        Some(stack.id)
+    }
+
+    /// Shows the next window in the stack of `window`, or the previous one if not
+    /// `forward`. Returns the window to bring to the front.
+    pub fn cycle(&mut self, window: W, forward: bool) -> Option<W> {
+        let stack = self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.is_stacked() && stack.windows.contains(&window))?;
+
+        let count = stack.windows.len();
+
+        stack.visible = if forward {
+            (stack.visible + 1) % count
+        } else {
+            (stack.visible + count - 1) % count
+        };
+
+        Some(stack.visible_window())
+    }
+
+    /// Moves the stacks in grid zones on the monitor along with the grid, after its
+    /// rows or columns changed. Spans past the last row or column shrink onto it.
+    /// Returns each window that needs moving, along with its new zone area.
+    pub fn follow_grid(
+        &mut self,
+        monitor: &str,
+        rows: usize,
+        columns: usize,
+        span_area: impl Fn(Span) -> Rect,
+    ) -> Vec<(W, Rect)> {
+        let mut moves = vec![];
+
+        if rows == 0 || columns == 0 {
+            return moves;
+        }
+
+        for stack in self.stacks.iter_mut() {
+            let ((top, left), (bottom, right)) = match stack.span {
+                Some(span) if stack.monitor == monitor => span,
+                _ => continue,
+            };
+
+            let span = (
+                (top.min(rows - 1), left.min(columns - 1)),
+                (bottom.min(rows - 1), right.min(columns - 1)),
+            );
+            let area = span_area(span);
+
+            stack.span = Some(span);
+
+            if area != stack.area {
+                stack.area = area;
+                moves.extend(stack.windows.iter().map(|window| (*window, area)));
+            }
+        }
+
+        // Stacks that ended up in the same zone become one
+        let mut idx = 0;
+        while idx < self.stacks.len() {
+            let stack = &self.stacks[idx];
+
+            match (0..idx).find(|other| {
+                self.stacks[*other].monitor == stack.monitor
+                    && self.stacks[*other].area == stack.area
+            }) {
+                Some(other) => {
+                    let windows = self.stacks.remove(idx).windows;
+                    self.stacks[other].windows.extend(windows);
+                }
+                None => idx += 1,
+            }
+        }
+
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: i32, width: i32) -> Rect {
+        Rect {
+            x,
+            y: 0,
+            width,
+            height: 1000,
+        }
+    }
+
+    #[test]
+    fn stacks_windows_in_same_zone() {
+        let mut stacks = Stacks::default();
+
+        let left = stacks.add(1, "A", area(0, 500), Some(((0, 0), (0, 0))));
+        assert_eq!(
+            stacks.add(2, "A", area(0, 500), Some(((0, 0), (0, 0)))),
+            left
+        );
+        let other_monitor = stacks.add(3, "B", area(0, 500), None);
+
+        assert_ne!(left, other_monitor);
+
+        let stack = stacks.get(left).unwrap();
+        assert_eq!(stack.windows, vec![1, 2]);
+        assert_eq!(stack.visible_window(), 2);
+        assert!(stack.is_stacked());
+        assert!(!stacks.get(other_monitor).unwrap().is_stacked());
+    }
+
+    #[test]
+    fn moving_window_leaves_old_stack() {
+        let mut stacks = Stacks::default();
+
+        let left = stacks.add(1, "A", area(0, 500), None);
+        stacks.add(2, "A", area(0, 500), None);
+        let right = stacks.add(2, "A", area(500, 500), None);
+
+        assert_eq!(stacks.get(left).unwrap().windows, vec![1]);
+        assert_eq!(stacks.get(right).unwrap().windows, vec![2]);
+
+        stacks.remove(1);
+
+        assert!(stacks.get(left).is_none());
+        assert_eq!(stacks.iter().count(), 1);
+    }
+
+    #[test]
+    fn cycles_and_shows() {
+        let mut stacks = Stacks::default();
+
+        let id = stacks.add(1, "A", area(0, 500), None);
+        stacks.add(2, "A", area(0, 500), None);
+        stacks.add(3, "A", area(0, 500), None);
+
+        assert_eq!(stacks.cycle(3, true), Some(1));
+        assert_eq!(stacks.cycle(1, false), Some(3));
+        assert_eq!(stacks.cycle(3, false), Some(2));
+
+        assert_eq!(stacks.show(2), None);
+        assert_eq!(stacks.show(1), Some(id));
+        assert_eq!(stacks.show(4), None);
+    }
+
+    #[test]
+    fn retain_keeps_visible_window() {
+        let mut stacks = Stacks::default();
+
+        let id = stacks.add(1, "A", area(0, 500), None);
+        stacks.add(2, "A", area(0, 500), None);
+        stacks.add(3, "A", area(0, 500), None);
+        stacks.show(2);
+
+        stacks.retain(|window, _| window != 1);
+        assert_eq!(stacks.get(id).unwrap().visible_window(), 2);
+
+        stacks.retain(|window, _| window != 2);
+        assert_eq!(stacks.get(id).unwrap().visible_window(), 3);
+    }
+
+    #[test]
+    fn follows_grid() {
+        let mut stacks = Stacks::default();
+        let span_area = |((_, left), (_, right)): Span| {
+            area(left as i32 * 100, (right - left + 1) as i32 * 100)
+        };
+
+        stacks.add(1, "A", area(200, 100), Some(((0, 2), (0, 2))));
+        stacks.add(2, "A", area(100, 100), Some(((0, 1), (0, 1))));
+        stacks.add(3, "B", area(200, 100), Some(((0, 2), (0, 2))));
+        stacks.add(4, "A", area(0, 50), None);
+
+        let moves = stacks.follow_grid("A", 1, 2, span_area);
+
+        assert_eq!(moves, vec![(1, area(100, 100))]);
+        assert_eq!(stacks.iter().count(), 3);
+
+        let merged = stacks
+            .iter()
+            .find(|stack| stack.windows.contains(&1))
+            .unwrap();
+        assert_eq!(merged.windows, vec![1, 2]);
+        assert_eq!(merged.span, Some(((0, 1), (0, 1))));
+
+        assert!(stacks.follow_grid("A", 0, 2, span_area).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use std::iter;
+
+use grout::dpi;
+use grout::Rect;
+
+use crate::window::{close_tab_strip, hide_tab_strip, place_tab_strip, spawn_tab_strip, Window};
+use crate::STACKS;
+
+/// Height of the tab strip over stacked windows, at 100% display scaling
+const TAB_HEIGHT: i32 = 24;
+
+/// Pixels a window's edges may be off from its zone while still counting as part of
+/// the stack there
+const STACK_TOLERANCE: i32 = 4;
+
+/// Tab strips shown over every zone holding more than one window
+#[derive(Default)]
+pub struct TabStrips {
+    strips: Vec<(usize, Window)>,
+    opening: Vec<usize>,
+}
+
+impl TabStrips {
+    /// Tab strip of the stack with `id` finished opening
+    pub fn opened(&mut self, id: usize, strip: Window) {
+        self.opening.retain(|opening| *opening != id);
+        self.strips.push((id, strip));
+
+        self.refresh();
+    }
+
+    /// Takes closed windows & windows moved out of their zone out of their stack,
+    /// then opens, moves & closes tab strips to match the stacks. Stacked windows are
+    /// fit below their strip, a window left alone in its zone gets all of it back.
+    pub fn refresh(&mut self) {
+        let mut zones = vec![];
+
+        {
+            let mut stacks = STACKS.lock().unwrap();
+
+            stacks.retain(|window, area| {
+                window.exists() && (window.is_minimized() || in_zone(window, area))
+            });
+
+            for stack in stacks.iter() {
+                zones.push((
+                    stack.id,
+                    stack.area,
+                    stack.windows.clone(),
+                    stack.visible_window(),
+                    stack.is_stacked(),
+                ));
+            }
+        }
+
+        // Strips get positioned after releasing the lock, as they paint while locking it
+        let (strips, closed) = self.strips.drain(..).partition(|(id, _)| {
+            zones
+                .iter()
+                .any(|(zone_id, _, _, _, stacked)| zone_id == id && *stacked)
+        });
+
+        self.strips = strips;
+
+        for (_, strip) in closed {
+            close_tab_strip(strip);
+        }
+
+        for (id, area, windows, visible_window, stacked) in zones {
+            let (tab_area, windows_area) = if stacked {
+                split_area(area, tab_height(visible_window))
+            } else {
+                (Rect::zero(), area)
+            };
+
+            // Moving a window raises it, so the visible window goes last to end up on top
+            let windows = windows
+                .into_iter()
+                .filter(|window| *window != visible_window)
+                .chain(iter::once(visible_window));
+
+            for mut window in windows {
+                if window.is_minimized() {
+                    continue;
+                }
+
+                let mut rect = windows_area;
+                rect.adjust_for_border(window.transparent_border());
+
+                if window.rect() != rect {
+                    window.set_pos(rect, None);
+                }
+            }
+
+            if !stacked {
+                continue;
+            }
+
+            match self.strips.iter().find(|(strip_id, _)| *strip_id == id) {
+                Some((_, strip)) if visible_window.is_minimized() => hide_tab_strip(*strip),
+                Some((_, strip)) => place_tab_strip(*strip, tab_area),
+                None if !self.opening.contains(&id) => {
+                    self.opening.push(id);
+
+                    spawn_tab_strip(id);
+                }
+                None => {}
+            }
+        }
+    }
+
+    pub fn close_all(&mut self) {
+        for (_, strip) in self.strips.drain(..) {
+            close_tab_strip(strip);
+        }
+    }
+}
+
+fn tab_height(window: Window) -> i32 {
+    dpi::scale(TAB_HEIGHT, window.monitor().dpi())
+}
+
+/// Splits a zone into the strip along its top and the area below it for the windows
+fn split_area(area: Rect, tab_height: i32) -> (Rect, Rect) {
+    let tab_area = Rect {
+        height: tab_height,
+        ..area
+    };
+
+    let windows_area = Rect {
+        y: area.y + tab_height,
+        height: area.height - tab_height,
+        ..area
+    };
+
+    (tab_area, windows_area)
+}
+
+/// Window fills the zone, or the part of it below the tab strip
+fn in_zone(window: Window, area: Rect) -> bool {
+    let mut rect = window.rect();
+    rect.remove_border(window.transparent_border());
+
+    let (_, windows_area) = split_area(area, tab_height(window));
+
+    close_to(rect, area) || close_to(rect, windows_area)
+}
+
+fn close_to(a: Rect, b: Rect) -> bool {
+    (a.x - b.x).abs() <= STACK_TOLERANCE
+        && (a.y - b.y).abs() <= STACK_TOLERANCE
+        && (a.width - b.width).abs() <= STACK_TOLERANCE
+        && (a.height - b.height).abs() <= STACK_TOLERANCE
+}
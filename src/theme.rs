@@ -0,0 +1,94 @@
+//! Colors of the grid & preview windows.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use anyhow::format_err;
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+use winapi::shared::windef::COLORREF;
+#[cfg(windows)]
+use winapi::um::wingdi::RGB;
+
+use crate::{Error, Result};
+
+/// Color in the config, written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    /// Color from its red, green & blue components
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Color { red, green, blue }
+    }
+
+    /// Color as passed to GDI
+    #[cfg(windows)]
+    pub fn colorref(self) -> COLORREF {
+        RGB(self.red, self.green, self.blue)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        let hex = text.trim().trim_start_matches('#');
+
+        let channel = |idx: usize| {
+            hex.get(idx..idx + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(red), Some(green), Some(blue)) => Ok(Color::rgb(red, green, blue)),
+            _ => Err(format_err!(
+                "Invalid color {}, expected #rrggbb such as #2c2c2c",
+                text
+            )),
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+/// Colors of the grid window & zone preview. Colors left out keep their default.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub background: Color,
+    pub tile: Color,
+    pub tile_hovered: Color,
+    pub tile_selected: Color,
+    pub tile_border: Color,
+    pub preview: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: Color::rgb(44, 44, 44),
+            tile: Color::rgb(178, 178, 178),
+            tile_hovered: Color::rgb(0, 100, 148),
+            tile_selected: Color::rgb(0, 77, 128),
+            tile_border: Color::rgb(0, 0, 0),
+            preview: Color::rgb(0, 77, 128),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Auto-tiling layouts, computing the area of every window on a monitor.
+
+use crate::geometry::split;
+use crate::Rect;
+
+/// Names of the available auto-tiling layouts, in the order they're cycled through
+pub const LAYOUTS: [&str; 3] = ["master_stack", "bsp", "monocle"];
+
+/// Computes a rectangle for each of `count` windows within `area`
+pub trait Layout {
+    fn arrange(&self, area: Rect, count: usize, margin: i32) -> Vec<Rect>;
+}
+
+/// Builds the layout named in `LAYOUTS`
+pub fn layout_from_name(name: &str, master_ratio: f32) -> Option<Box<dyn Layout>> {
+    match name {
+        "master_stack" => Some(Box::new(MasterStack {
+            ratio: master_ratio,
+        })),
+        "bsp" => Some(Box::new(Bsp)),
+        "monocle" => Some(Box::new(Monocle)),
+        _ => None,
+    }
+}
+
+/// First window takes `ratio` of the width, the rest are stacked in the remaining column
+pub struct MasterStack {
+    pub ratio: f32,
+}
+
+impl Layout for MasterStack {
+    fn arrange(&self, area: Rect, count: usize, margin: i32) -> Vec<Rect> {
+        if count < 2 {
+            return vec![area; count];
+        }
+
+        let available = (area.width - margin).max(0);
+        let master_width = (available as f32 * self.ratio.clamp(0.1, 0.9)).round() as i32;
+
+        let master = Rect {
+            width: master_width,
+            ..area
+        };
+
+        let stack_x = area.x + master_width + margin;
+        let stack_width = available - master_width;
+
+        let mut rects = vec![master];
+
+        rects.extend(
+            split(area.height, &vec![1; count - 1], margin)
+                .into_iter()
+                .map(|(y, height)| Rect {
+                    x: stack_x,
+                    y: area.y + y,
+                    width: stack_width,
+                    height,
+                }),
+        );
+
+        rects
+    }
+}
+
+/// Each window splits the space left over by the previous one in half, along its
+/// longest side
+pub struct Bsp;
+
+impl Layout for Bsp {
+    fn arrange(&self, area: Rect, count: usize, margin: i32) -> Vec<Rect> {
+        let mut rects = Vec::with_capacity(count);
+        let mut remaining = area;
+
+        for idx in 0..count {
+            if idx == count - 1 {
+                rects.push(remaining);
+                break;
+            }
+
+            if remaining.width >= remaining.height {
+                let halves = split(remaining.width, &[1, 1], margin);
+
+                rects.push(Rect {
+                    x: remaining.x + halves[0].0,
+                    width: halves[0].1,
+                    ..remaining
+                });
+                remaining = Rect {
+                    x: remaining.x + halves[1].0,
+                    width: halves[1].1,
+                    ..remaining
+                };
+            } else {
+                let halves = split(remaining.height, &[1, 1], margin);
+
+                rects.push(Rect {
+                    y: remaining.y + halves[0].0,
+                    height: halves[0].1,
+                    ..remaining
+                });
+                remaining = Rect {
+                    y: remaining.y + halves[1].0,
+                    height: halves[1].1,
+                    ..remaining
+                };
+            }
+        }
+
+        rects
+    }
+}
+
+/// Every window fills the whole area
+pub struct Monocle;
+
+impl Layout for Monocle {
+    fn arrange(&self, area: Rect, count: usize, _margin: i32) -> Vec<Rect> {
+        vec![area; count]
+    }
+}
+
+/// Drops windows from `order` that are no longer `visible` and appends new ones,
+/// keeping existing windows in place. Returns true if the order changed.
+pub fn sync_order<W: Copy + PartialEq>(order: &mut Vec<W>, visible: &[W]) -> bool {
+    let previous = order.clone();
+
+    order.retain(|window| visible.contains(window));
+
+    for window in visible {
+        if !order.contains(window) {
+            order.push(*window);
+        }
+    }
+
+    *order != previous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 100,
+        y: 50,
+        width: 1000,
+        height: 600,
+    };
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn master_stack() {
+        let layout = MasterStack { ratio: 0.6 };
+
+        assert_eq!(layout.arrange(AREA, 0, 10), vec![]);
+        assert_eq!(layout.arrange(AREA, 1, 10), vec![AREA]);
+        assert_eq!(
+            layout.arrange(AREA, 2, 10),
+            vec![rect(100, 50, 594, 600), rect(704, 50, 396, 600)]
+        );
+        assert_eq!(
+            layout.arrange(AREA, 4, 10),
+            vec![
+                rect(100, 50, 594, 600),
+                rect(704, 50, 396, 193),
+                rect(704, 253, 396, 193),
+                rect(704, 456, 396, 194),
+            ]
+        );
+    }
+
+    #[test]
+    fn master_ratio_is_clamped() {
+        let rects = MasterStack { ratio: 2.0 }.arrange(AREA, 2, 0);
+
+        assert_eq!(rects[0].width, 900);
+        assert_eq!(rects[1], rect(1000, 50, 100, 600));
+    }
+
+    #[test]
+    fn bsp() {
+        assert_eq!(Bsp.arrange(AREA, 0, 10), vec![]);
+        assert_eq!(Bsp.arrange(AREA, 1, 10), vec![AREA]);
+        assert_eq!(
+            Bsp.arrange(AREA, 3, 10),
+            vec![
+                rect(100, 50, 495, 600),
+                rect(605, 50, 495, 295),
+                rect(605, 355, 495, 295),
+            ]
+        );
+        assert_eq!(
+            Bsp.arrange(AREA, 4, 0)[2..],
+            [rect(600, 350, 250, 300), rect(850, 350, 250, 300)]
+        );
+    }
+
+    #[test]
+    fn layouts_stay_within_area() {
+        for name in LAYOUTS.iter() {
+            let layout = layout_from_name(name, 0.5).unwrap();
+
+            for count in 0..8 {
+                let rects = layout.arrange(AREA, count, 8);
+
+                assert_eq!(rects.len(), count, "{}", name);
+                assert!(rects.iter().all(|rect| rect.x >= AREA.x
+                    && rect.y >= AREA.y
+                    && rect.x + rect.width <= AREA.x + AREA.width
+                    && rect.y + rect.height <= AREA.y + AREA.height));
+            }
+        }
+
+        assert!(layout_from_name("spiral", 0.5).is_none());
+    }
+
+    #[test]
+    fn monocle() {
+        assert_eq!(Monocle.arrange(AREA, 3, 10), vec![AREA; 3]);
+    }
+
+    #[test]
+    fn sync_order_keeps_existing_windows_in_place() {
+        let mut order = vec![];
+
+        assert!(sync_order(&mut order, &[1, 2, 3]));
+        assert_eq!(order, vec![1, 2, 3]);
+
+        assert!(!sync_order(&mut order, &[3, 1, 2]));
+        assert_eq!(order, vec![1, 2, 3]);
+
+        assert!(sync_order(&mut order, &[4, 3, 1]));
+        assert_eq!(order, vec![1, 3, 4]);
+
+        assert!(sync_order(&mut order, &[]));
+        assert!(order.is_empty());
+    }
+}
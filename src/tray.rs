@@ -4,43 +4,51 @@ use std::thread;
 
 use winapi::shared::{
     minwindef::{LOWORD, LPARAM, LRESULT, UINT, WPARAM},
-    windef::{HWND, POINT},
+    windef::{HMENU, HWND, POINT},
 };
 use winapi::um::libloaderapi::GetModuleHandleW;
 use winapi::um::shellapi::{
-    ShellExecuteW, Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE,
-    NOTIFYICONDATAW,
+    ShellExecuteW, Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_MESSAGE, NIF_TIP, NIIF_WARNING,
+    NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW,
 };
 use winapi::um::wingdi::{CreateSolidBrush, RGB};
 use winapi::um::winuser::{
     CheckMenuItem, CreateIconFromResourceEx, CreatePopupMenu, CreateWindowExW, DefWindowProcW,
-    DestroyMenu, DispatchMessageW, GetCursorPos, GetMessageW, InsertMenuW, MessageBoxW,
-    PostMessageW, PostQuitMessage, RegisterClassExW, SendMessageW, SetFocus, SetForegroundWindow,
-    SetMenuDefaultItem, SetMenuItemBitmaps, TrackPopupMenu, TranslateMessage, LR_DEFAULTCOLOR,
-    MB_ICONINFORMATION, MB_OK, MF_BYPOSITION, MF_CHECKED, MF_STRING, MF_UNCHECKED, SW_SHOW,
-    TPM_LEFTALIGN, TPM_NONOTIFY, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_APP, WM_CLOSE, WM_COMMAND,
-    WM_CREATE, WM_INITMENUPOPUP, WM_LBUTTONDBLCLK, WM_RBUTTONUP, WNDCLASSEXW, WS_EX_NOACTIVATE,
+    DestroyMenu, DispatchMessageW, FindWindowW, GetCursorPos, GetMessageW, InsertMenuW,
+    MessageBoxW, PostMessageW, PostQuitMessage, RegisterClassExW, SendMessageW, SetFocus,
+    SetForegroundWindow, SetMenuDefaultItem, SetMenuItemBitmaps, TrackPopupMenu, TranslateMessage,
+    LR_DEFAULTCOLOR, MB_ICONINFORMATION, MB_OK, MF_BYPOSITION, MF_CHECKED, MF_GRAYED, MF_POPUP,
+    MF_STRING, MF_UNCHECKED, SW_SHOW, TPM_LEFTALIGN, TPM_NONOTIFY, TPM_RETURNCMD, TPM_RIGHTBUTTON,
+    WM_APP, WM_CLOSE, WM_COMMAND, WM_CREATE, WM_INITMENUPOPUP, WM_LBUTTONDBLCLK, WM_RBUTTONUP,
+    WNDCLASSEXW, WS_EX_NOACTIVATE,
 };
 
+use grout::config;
+use grout::snapshot::{list_snapshots, DEFAULT_SNAPSHOT};
+
 use crate::autostart;
 use crate::common::show_msg_box;
-use crate::config;
+use crate::hotkey::BindingState;
 use crate::str_to_wide;
 use crate::Message;
 use crate::CHANNEL;
-use crate::CONFIG;
+use crate::{CONFIG, HOTKEYS};
 
 const ID_ABOUT: u16 = 2000;
 const ID_EXIT: u16 = 2001;
 const ID_CONFIG: u16 = 2002;
 const ID_AUTOSTART: u16 = 2003;
+const ID_SAVE_SNAPSHOT: u16 = 2004;
+const ID_RESTORE_SNAPSHOT: u16 = 2100; // + index of snapshot
+const WM_HOTKEY_CONFLICT: u32 = WM_APP + 2;
+const TRAY_CLASS: &str = "Grout Tray";
 static mut MODAL_SHOWN: bool = false;
 
 pub unsafe fn spawn_sys_tray() {
     thread::spawn(|| {
         let hInstance = GetModuleHandleW(ptr::null());
 
-        let class_name = str_to_wide!("Grout Tray");
+        let class_name = str_to_wide!(TRAY_CLASS);
 
         let mut class = mem::zeroed::<WNDCLASSEXW>();
         class.cbSize = mem::size_of::<WNDCLASSEXW>() as u32;
@@ -105,6 +113,105 @@ unsafe fn add_icon(hwnd: HWND) {
     Shell_NotifyIconW(NIM_ADD, &mut icon_data);
 }
 
+/// Shows a notification for hotkeys that couldn't be registered, along with why.
+/// Conflicts found before the tray icon was added are shown once it is.
+pub fn notify_hotkey_conflicts() {
+    unsafe {
+        let hwnd = tray_window();
+
+        if !hwnd.is_null() {
+            PostMessageW(hwnd, WM_HOTKEY_CONFLICT, 0, 0);
+        }
+    }
+}
+
+/// Shows a notification from the tray icon. Nothing is shown if the icon wasn't added yet.
+pub fn notify(title: &str, message: &str) {
+    unsafe {
+        let hwnd = tray_window();
+
+        if !hwnd.is_null() {
+            show_notification(hwnd, title, message);
+        }
+    }
+}
+
+unsafe fn tray_window() -> HWND {
+    let class_name = str_to_wide!(TRAY_CLASS);
+
+    FindWindowW(class_name.as_ptr(), ptr::null())
+}
+
+unsafe fn show_hotkey_conflicts(hwnd: HWND) {
+    let mut disabled = false;
+
+    let conflicts = HOTKEYS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .filter(|binding| !binding.reported)
+        .filter_map(|binding| {
+            let conflict = match &binding.state {
+                BindingState::Fallback { fallback, reason } => format!(
+                    "{}: {}, using {} instead",
+                    binding.hotkey_type.description(),
+                    reason,
+                    fallback
+                ),
+                BindingState::Failed(reason) => {
+                    disabled = true;
+
+                    format!(
+                        "{}: {}, so it was disabled",
+                        binding.hotkey_type.description(),
+                        reason
+                    )
+                }
+                _ => return None,
+            };
+
+            binding.reported = true;
+
+            Some(conflict)
+        })
+        .collect::<Vec<_>>();
+
+    if conflicts.is_empty() {
+        return;
+    }
+
+    let mut message = conflicts.join("\n");
+
+    if disabled {
+        message.push_str("\nSet a fallback under hotkey_fallbacks in the config");
+    }
+
+    show_notification(hwnd, "Hotkeys couldn't be registered", &message);
+}
+
+unsafe fn show_notification(hwnd: HWND, title: &str, message: &str) {
+    let mut icon_data: NOTIFYICONDATAW = mem::zeroed();
+    icon_data.cbSize = mem::size_of::<NOTIFYICONDATAW>() as u32;
+    icon_data.hWnd = hwnd;
+    icon_data.uID = 1;
+    icon_data.uFlags = NIF_INFO;
+    icon_data.dwInfoFlags = NIIF_WARNING;
+
+    copy_wide(title, &mut icon_data.szInfoTitle);
+    copy_wide(message, &mut icon_data.szInfo);
+
+    Shell_NotifyIconW(NIM_MODIFY, &mut icon_data);
+}
+
+/// Copies as much of `text` as fits into `buffer`, leaving room for the terminating null
+fn copy_wide(text: &str, buffer: &mut [u16]) {
+    let capacity = buffer.len() - 1;
+
+    for (slot, unit) in buffer[..capacity].iter_mut().zip(text.encode_utf16()) {
+        *slot = unit;
+    }
+}
+
 unsafe fn remove_icon(hwnd: HWND) {
     let mut icon_data: NOTIFYICONDATAW = mem::zeroed();
     icon_data.hWnd = hwnd;
@@ -123,6 +230,9 @@ unsafe fn show_popup_menu(hwnd: HWND) {
     let mut about = str_to_wide!("About...");
     let mut auto_start = str_to_wide!("Launch at startup");
     let mut open_config = str_to_wide!("Open Config");
+    let mut hotkeys = str_to_wide!("Hotkeys");
+    let mut save_snapshot = str_to_wide!("Save Layout");
+    let mut restore_snapshot = str_to_wide!("Restore Layout");
     let mut exit = str_to_wide!("Exit");
 
     InsertMenuW(
@@ -162,6 +272,55 @@ unsafe fn show_popup_menu(hwnd: HWND) {
     InsertMenuW(
         menu,
         3,
+        MF_BYPOSITION | MF_POPUP,
+        hotkey_menu() as usize,
+        hotkeys.as_mut_ptr(),
+    );
+
+    InsertMenuW(
+        menu,
+        4,
+        MF_BYPOSITION | MF_STRING,
+        ID_SAVE_SNAPSHOT as usize,
+        save_snapshot.as_mut_ptr(),
+    );
+
+    let snapshot_menu = CreatePopupMenu();
+    let snapshots = list_snapshots();
+
+    if snapshots.is_empty() {
+        let mut empty = str_to_wide!("No saved layouts");
+        InsertMenuW(
+            snapshot_menu,
+            0,
+            MF_BYPOSITION | MF_STRING | MF_GRAYED,
+            0,
+            empty.as_mut_ptr(),
+        );
+    }
+
+    for (idx, name) in snapshots.iter().enumerate() {
+        let mut name = str_to_wide!(name);
+        InsertMenuW(
+            snapshot_menu,
+            idx as u32,
+            MF_BYPOSITION | MF_STRING,
+            ID_RESTORE_SNAPSHOT as usize + idx,
+            name.as_mut_ptr(),
+        );
+    }
+
+    InsertMenuW(
+        menu,
+        5,
+        MF_BYPOSITION | MF_POPUP,
+        snapshot_menu as usize,
+        restore_snapshot.as_mut_ptr(),
+    );
+
+    InsertMenuW(
+        menu,
+        6,
         MF_BYPOSITION | MF_STRING,
         ID_EXIT as usize,
         exit.as_mut_ptr(),
@@ -189,6 +348,35 @@ unsafe fn show_popup_menu(hwnd: HWND) {
     DestroyMenu(menu);
 }
 
+/// Every hotkey in the config, checked if it's active & grayed out if it's disabled
+unsafe fn hotkey_menu() -> HMENU {
+    let hotkey_menu = CreatePopupMenu();
+
+    for (idx, binding) in HOTKEYS.lock().unwrap().iter().enumerate() {
+        let (hotkey, flags) = match &binding.state {
+            BindingState::Registering => (binding.hotkey.clone(), MF_UNCHECKED),
+            BindingState::Active => (binding.hotkey.clone(), MF_CHECKED),
+            BindingState::Fallback { fallback, .. } => (
+                format!("{} (instead of {})", fallback, binding.hotkey),
+                MF_CHECKED,
+            ),
+            BindingState::Failed(_) => (format!("{} (disabled)", binding.hotkey), MF_GRAYED),
+        };
+
+        let mut item = str_to_wide!(format!("{}\t{}", binding.hotkey_type.description(), hotkey));
+
+        InsertMenuW(
+            hotkey_menu,
+            idx as u32,
+            MF_BYPOSITION | MF_STRING | flags,
+            0,
+            item.as_mut_ptr(),
+        );
+    }
+
+    hotkey_menu
+}
+
 unsafe fn show_about() {
     let mut title = str_to_wide!("About");
 
@@ -216,6 +404,11 @@ unsafe extern "system" fn callback(
     match Msg {
         WM_CREATE => {
             add_icon(hWnd);
+            PostMessageW(hWnd, WM_HOTKEY_CONFLICT, 0, 0);
+            return 0;
+        }
+        WM_HOTKEY_CONFLICT => {
+            show_hotkey_conflicts(hWnd);
             return 0;
         }
         WM_CLOSE => {
@@ -258,10 +451,7 @@ unsafe extern "system" fn callback(
                     };
                 }
                 ID_CONFIG => {
-                    if let Some(mut config_path) = dirs::config_dir() {
-                        config_path.push("grout");
-                        config_path.push("config.yml");
-
+                    if let Ok(config_path) = config::config_path() {
                         if config_path.exists() {
                             let mut operation = str_to_wide!("open");
                             let mut config_path = str_to_wide!(config_path.to_str().unwrap());
@@ -277,9 +467,23 @@ unsafe extern "system" fn callback(
                         }
                     }
                 }
+                ID_SAVE_SNAPSHOT => {
+                    let _ = &CHANNEL
+                        .0
+                        .clone()
+                        .send(Message::SaveSnapshot(DEFAULT_SNAPSHOT.to_owned()));
+                }
                 ID_EXIT => {
                     PostMessageW(hWnd, WM_CLOSE, 0, 0);
                 }
+                id if id >= ID_RESTORE_SNAPSHOT => {
+                    if let Some(name) = list_snapshots().get((id - ID_RESTORE_SNAPSHOT) as usize) {
+                        let _ = &CHANNEL
+                            .0
+                            .clone()
+                            .send(Message::RestoreSnapshot(name.clone()));
+                    }
+                }
                 _ => {}
             }
 
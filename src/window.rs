@@ -1,18 +1,33 @@
 use std::mem;
 use std::ptr;
 
+use winapi::shared::minwindef::{BOOL, FALSE, LPARAM, MAX_PATH, TRUE};
 use winapi::shared::windef::HWND;
+use winapi::um::dwmapi::{DwmGetWindowAttribute, DWMWA_CLOAKED};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winbase::QueryFullProcessImageNameW;
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
 use winapi::um::winuser::{
-    GetWindowInfo, GetWindowRect, SetWindowPos, ShowWindow, SWP_NOACTIVATE, SW_RESTORE, WINDOWINFO,
+    EnumWindows, GetClassNameW, GetWindow, GetWindowInfo, GetWindowPlacement, GetWindowRect,
+    GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
+    IsWindowVisible, IsZoomed, MonitorFromWindow, SetForegroundWindow, SetWindowPos, ShowWindow,
+    GW_OWNER, MONITOR_DEFAULTTONEAREST, SWP_NOACTIVATE, SW_MAXIMIZE, SW_MINIMIZE, SW_RESTORE,
+    WINDOWINFO, WINDOWPLACEMENT, WS_EX_TOOLWINDOW,
 };
 
-use crate::common::Rect;
+use grout::Rect;
+
+use crate::monitor::Monitor;
 
 mod grid;
 pub use grid::spawn_grid_window;
 
 mod preview;
-pub use preview::spawn_preview_window;
+pub use preview::{set_preview_color, spawn_preview_window};
+
+mod tabs;
+pub use tabs::{close_tab_strip, hide_tab_strip, place_tab_strip, spawn_tab_strip};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Window(pub HWND);
@@ -74,6 +89,167 @@ impl Window {
             ShowWindow(self.0, SW_RESTORE);
         };
     }
+
+    pub fn maximize(&mut self) {
+        unsafe {
+            ShowWindow(self.0, SW_MAXIMIZE);
+        };
+    }
+
+    pub fn minimize(&mut self) {
+        unsafe {
+            ShowWindow(self.0, SW_MINIMIZE);
+        };
+    }
+
+    /// Brings the window to the front and gives it focus
+    pub fn activate(self) {
+        unsafe {
+            SetForegroundWindow(self.0);
+        }
+    }
+
+    /// Rect the window has when it's neither maximized nor minimized, in screen
+    /// coordinates
+    pub fn normal_rect(self) -> Rect {
+        if !self.is_minimized() && !self.is_maximized() {
+            return self.rect();
+        }
+
+        unsafe {
+            let mut placement: WINDOWPLACEMENT = mem::zeroed();
+            placement.length = mem::size_of::<WINDOWPLACEMENT>() as u32;
+
+            GetWindowPlacement(self.0, &mut placement);
+
+            // Placements are in workspace coordinates, which are offset from screen
+            // coordinates by the taskbar when it's on the top or left of the monitor
+            let monitor = self.monitor();
+            let work_area = monitor.work_area();
+            let bounds = monitor.bounds();
+
+            let mut rect: Rect = placement.rcNormalPosition.into();
+            rect.x += work_area.x - bounds.x;
+            rect.y += work_area.y - bounds.y;
+
+            rect
+        }
+    }
+
+    /// File name of the executable that owns the window, such as `code.exe`
+    pub fn exe_name(self) -> String {
+        unsafe {
+            let mut process_id = 0;
+            GetWindowThreadProcessId(self.0, &mut process_id);
+
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id);
+            if process.is_null() {
+                return String::new();
+            }
+
+            let mut path = [0u16; MAX_PATH];
+            let mut len = path.len() as u32;
+
+            let result = QueryFullProcessImageNameW(process, 0, path.as_mut_ptr(), &mut len);
+            CloseHandle(process);
+
+            if result == 0 {
+                return String::new();
+            }
+
+            let path = String::from_utf16_lossy(&path[..len as usize]);
+            path.rsplit('\\').next().unwrap_or_default().to_owned()
+        }
+    }
+
+    pub fn title(self) -> String {
+        unsafe {
+            let len = GetWindowTextLengthW(self.0);
+            let mut title = vec![0u16; len as usize + 1];
+
+            let len = GetWindowTextW(self.0, title.as_mut_ptr(), title.len() as i32);
+
+            String::from_utf16_lossy(&title[..len as usize])
+        }
+    }
+
+    pub fn class_name(self) -> String {
+        unsafe {
+            let mut class_name = [0u16; 256];
+
+            let len = GetClassNameW(self.0, class_name.as_mut_ptr(), class_name.len() as i32);
+
+            String::from_utf16_lossy(&class_name[..len as usize])
+        }
+    }
+
+    /// Window handle still refers to an open window
+    pub fn exists(self) -> bool {
+        unsafe { IsWindow(self.0) != 0 }
+    }
+
+    pub fn monitor(self) -> Monitor {
+        unsafe { Monitor(MonitorFromWindow(self.0, MONITOR_DEFAULTTONEAREST)) }
+    }
+
+    pub fn is_minimized(self) -> bool {
+        unsafe { IsIconic(self.0) != 0 }
+    }
+
+    pub fn is_maximized(self) -> bool {
+        unsafe { IsZoomed(self.0) != 0 }
+    }
+
+    /// Visible, unowned application window that shows up in the taskbar
+    pub fn is_app_window(self) -> bool {
+        unsafe { self.is_visible_app_window() }
+    }
+
+    unsafe fn is_visible_app_window(self) -> bool {
+        if IsWindowVisible(self.0) == 0 || !GetWindow(self.0, GW_OWNER).is_null() {
+            return false;
+        }
+
+        if self.info().extended_styles & WS_EX_TOOLWINDOW != 0 || self.title().is_empty() {
+            return false;
+        }
+
+        // UWP apps keep hidden windows around that are cloaked rather than invisible
+        let mut cloaked: u32 = 0;
+        DwmGetWindowAttribute(
+            self.0,
+            DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as *mut _,
+            mem::size_of::<u32>() as u32,
+        );
+
+        cloaked == 0
+    }
+}
+
+/// All visible top-level application windows, including minimized ones
+pub fn get_visible_windows() -> Vec<Window> {
+    let mut windows: Vec<Window> = vec![];
+
+    unsafe {
+        EnumWindows(
+            Some(enum_windows_callback),
+            &mut windows as *mut Vec<Window> as LPARAM,
+        );
+    }
+
+    windows
+}
+
+unsafe extern "system" fn enum_windows_callback(hwnd: HWND, data: LPARAM) -> BOOL {
+    let windows = &mut *(data as *mut Vec<Window>);
+    let window = Window(hwnd);
+
+    if window.is_visible_app_window() {
+        windows.push(window);
+    }
+
+    TRUE
 }
 
 impl Default for Window {
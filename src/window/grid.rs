@@ -3,7 +3,7 @@ use std::ptr;
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{select, Receiver};
+use crossbeam_channel::{select, Receiver, Sender};
 
 use winapi::shared::{
     minwindef::{HIWORD, LOWORD, LPARAM, LRESULT, UINT, WPARAM},
@@ -15,16 +15,21 @@ use winapi::um::wingdi::{CreateSolidBrush, RGB};
 use winapi::um::winuser::{
     CreateWindowExW, DefWindowProcW, DispatchMessageW, InvalidateRect, LoadCursorW, PeekMessageW,
     RegisterClassExW, SendMessageW, TranslateMessage, IDC_ARROW, VK_CONTROL, VK_DOWN, VK_ESCAPE,
-    VK_F1, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_LEFT, VK_RIGHT, VK_SHIFT, VK_UP, WM_KEYDOWN,
-    WM_KEYUP, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSELEAVE, WM_MOUSEMOVE, WM_PAINT, WNDCLASSEXW,
-    WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP,
+    VK_LEFT, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_TAB, VK_UP, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDOWN,
+    WM_LBUTTONUP, WM_MOUSELEAVE, WM_MOUSEMOVE, WM_PAINT, WNDCLASSEXW, WS_EX_TOOLWINDOW,
+    WS_EX_TOPMOST, WS_POPUP,
 };
 
-use crate::common::{get_work_area, Rect};
+use grout::geometry::Direction;
+use grout::Rect;
+
+use crate::common::get_work_area;
+use crate::grid::Grid;
+use crate::monitor::get_active_monitor;
 use crate::str_to_wide;
 use crate::window::Window;
 use crate::Message;
-use crate::{CHANNEL, GRID};
+use crate::{CHANNEL, GRID, HISTORY, STACKS};
 
 pub fn spawn_grid_window(close_msg: Receiver<()>) {
     thread::spawn(move || unsafe {
@@ -97,6 +102,13 @@ unsafe extern "system" fn callback(
                 let _ = sender.send(Message::CloseWindows);
                 false
             }
+            VK_RETURN => apply_selection(&mut GRID.lock().unwrap(), sender),
+            VK_TAB => {
+                let mut grid = GRID.lock().unwrap();
+                let forward = !grid.shift_down;
+
+                grid.cycle_active_window(forward)
+            }
             VK_CONTROL => {
                 GRID.lock().unwrap().control_down = true;
                 false
@@ -109,29 +121,41 @@ unsafe extern "system" fn callback(
                 if GRID.lock().unwrap().control_down {
                     GRID.lock().unwrap().add_column();
                     GRID.lock().unwrap().reposition();
+                    let _ = sender.send(Message::GridChanged);
+                    false
+                } else {
+                    move_cursor(Direction::Right, sender)
                 }
-                false
             }
             VK_LEFT => {
                 if GRID.lock().unwrap().control_down {
                     GRID.lock().unwrap().remove_column();
                     GRID.lock().unwrap().reposition();
+                    let _ = sender.send(Message::GridChanged);
+                    false
+                } else {
+                    move_cursor(Direction::Left, sender)
                 }
-                false
             }
             VK_UP => {
                 if GRID.lock().unwrap().control_down {
                     GRID.lock().unwrap().add_row();
                     GRID.lock().unwrap().reposition();
+                    let _ = sender.send(Message::GridChanged);
+                    false
+                } else {
+                    move_cursor(Direction::Up, sender)
                 }
-                false
             }
             VK_DOWN => {
                 if GRID.lock().unwrap().control_down {
                     GRID.lock().unwrap().remove_row();
                     GRID.lock().unwrap().reposition();
+                    let _ = sender.send(Message::GridChanged);
+                    false
+                } else {
+                    move_cursor(Direction::Down, sender)
                 }
-                false
             }
             _ => false,
         },
@@ -144,31 +168,15 @@ unsafe extern "system" fn callback(
                 GRID.lock().unwrap().shift_down = false;
                 false
             }
-            VK_F1 => {
-                let _ = sender.send(Message::ProfileChange("Default"));
-                false
-            }
-            VK_F2 => {
-                let _ = sender.send(Message::ProfileChange("Profile2"));
-                false
-            }
-            VK_F3 => {
-                let _ = sender.send(Message::ProfileChange("Profile3"));
-                false
-            }
-            VK_F4 => {
-                let _ = sender.send(Message::ProfileChange("Profile4"));
-                false
-            }
-            VK_F5 => {
-                let _ = sender.send(Message::ProfileChange("Profile5"));
-                false
-            }
-            VK_F6 => {
-                let _ = sender.send(Message::ProfileChange("Profile6"));
+            vk_code => {
+                let profile = GRID.lock().unwrap().profile_for_key(vk_code);
+
+                if let Some(profile) = profile {
+                    let _ = sender.send(Message::ProfileChange(profile));
+                }
+
                 false
             }
-            _ => false,
         },
         WM_MOUSEMOVE => {
             let x = LOWORD(lParam as u32) as i32;
@@ -190,38 +198,25 @@ unsafe extern "system" fn callback(
 
             let mut grid = GRID.lock().unwrap();
 
-            let repaint = grid.select_tile((x, y));
+            if let Some(window) = grid.listed_window_at((x, y)) {
+                let repaint = grid.active_window != Some(window);
 
-            grid.cursor_down = true;
+                grid.active_window = Some(window);
 
-            repaint
+                repaint
+            } else {
+                let repaint = grid.select_tile((x, y));
+
+                grid.cursor_down = true;
+
+                repaint
+            }
         }
         WM_LBUTTONUP => {
             let mut grid = GRID.lock().unwrap();
 
-            let repaint = if let Some(mut rect) = grid.selected_area() {
-                if let Some(mut active_window) = grid.active_window {
-                    if grid.previous_resize != Some((active_window, rect)) {
-                        active_window.restore();
-
-                        rect.adjust_for_border(active_window.transparent_border());
-
-                        active_window.set_pos(rect, None);
-
-                        grid.previous_resize = Some((active_window, rect));
-
-                        if grid.quick_resize {
-                            let _ = sender.send(Message::CloseWindows);
-                        }
-                    }
-
-                    grid.unselect_all_tiles();
-                }
-
-                true
-            } else {
-                false
-            };
+            // Picking a window from the list doesn't place it
+            let repaint = grid.cursor_down && apply_selection(&mut grid, sender);
 
             grid.cursor_down = false;
 
@@ -253,3 +248,59 @@ unsafe extern "system" fn callback(
 
     DefWindowProcW(hWnd, Msg, wParam, lParam)
 }
+
+/// Moves the active window to the selected area. Returns true if the grid needs
+/// to be repainted.
+unsafe fn apply_selection(grid: &mut Grid, sender: &Sender<Message>) -> bool {
+    if let Some(mut rect) = grid.selected_area() {
+        if let Some(mut active_window) = grid.active_window {
+            let active_rect = active_window.rect();
+            let zone_area = rect;
+
+            active_window.restore();
+
+            rect.adjust_for_border(active_window.transparent_border());
+
+            if active_rect != rect {
+                active_window.set_pos(rect, None);
+
+                HISTORY
+                    .lock()
+                    .unwrap()
+                    .record(active_window, active_rect, rect);
+
+                if grid.closes_after_placement() {
+                    let _ = sender.send(Message::CloseWindows);
+                }
+            }
+
+            // Windows placed in the same zone get stacked behind tabs
+            STACKS.lock().unwrap().add(
+                active_window,
+                &get_active_monitor().descriptor().id(),
+                zone_area,
+                grid.selected_span(),
+            );
+            let _ = sender.send(Message::StacksChanged);
+
+            grid.unselect_all_tiles();
+        }
+
+        true
+    } else {
+        false
+    }
+}
+
+fn move_cursor(direction: Direction, sender: &Sender<Message>) -> bool {
+    let mut grid = GRID.lock().unwrap();
+    let extend = grid.shift_down;
+
+    if let Some(rect) = unsafe { grid.move_cursor(direction, extend) } {
+        let _ = sender.send(Message::HighlightZone(rect));
+
+        true
+    } else {
+        false
+    }
+}
@@ -10,18 +10,21 @@ use winapi::shared::{
     windef::HWND,
 };
 use winapi::um::libloaderapi::GetModuleHandleW;
-use winapi::um::wingdi::{CreateSolidBrush, RGB};
+use winapi::um::wingdi::{CreateSolidBrush, DeleteObject, RGB};
 
 use winapi::um::winuser::{
-    CreateWindowExW, DefWindowProcW, DispatchMessageW, PeekMessageW, RegisterClassExW,
-    SetLayeredWindowAttributes, TranslateMessage, LWA_ALPHA, WNDCLASSEXW, WS_EX_LAYERED,
-    WS_EX_NOACTIVATE, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP, WS_SYSMENU, WS_VISIBLE,
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, InvalidateRect, PeekMessageW,
+    RegisterClassExW, SetClassLongPtrW, SetLayeredWindowAttributes, TranslateMessage,
+    GCLP_HBRBACKGROUND, LWA_ALPHA, WNDCLASSEXW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOPMOST,
+    WS_EX_TRANSPARENT, WS_POPUP, WS_SYSMENU, WS_VISIBLE,
 };
 
+use grout::theme::Color;
+
 use crate::str_to_wide;
 use crate::window::Window;
 use crate::Message;
-use crate::CHANNEL;
+use crate::{CHANNEL, GRID};
 
 pub fn spawn_preview_window(close_msg: Receiver<()>) {
     thread::spawn(move || unsafe {
@@ -55,6 +58,9 @@ pub fn spawn_preview_window(close_msg: Receiver<()>) {
 
         SetLayeredWindowAttributes(hwnd, 0, 107, LWA_ALPHA);
 
+        let color = GRID.lock().unwrap().theme.preview;
+        set_preview_color(Window(hwnd), color);
+
         let _ = &CHANNEL.0.clone().send(Message::PreviewWindow(Window(hwnd)));
 
         let mut msg = mem::zeroed();
@@ -74,6 +80,21 @@ pub fn spawn_preview_window(close_msg: Receiver<()>) {
     });
 }
 
+/// Fills the preview with `color`, such as the preview color of the active profile's
+/// theme. The class brush is shared, so this also applies to previews opened later.
+pub fn set_preview_color(preview: Window, color: Color) {
+    unsafe {
+        let brush = CreateSolidBrush(color.colorref());
+        let previous = SetClassLongPtrW(preview.0, GCLP_HBRBACKGROUND, brush as isize);
+
+        if previous != 0 {
+            DeleteObject(previous as *mut _);
+        }
+
+        InvalidateRect(preview.0, ptr::null(), 1);
+    }
+}
+
 unsafe extern "system" fn callback(
     hWnd: HWND,
     Msg: UINT,
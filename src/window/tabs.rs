@@ -0,0 +1,208 @@
+use std::mem;
+use std::ptr;
+use std::thread;
+
+use winapi::shared::{
+    minwindef::{LOWORD, LPARAM, LRESULT, UINT, WPARAM},
+    windef::HWND,
+};
+use winapi::um::libloaderapi::GetModuleHandleW;
+use winapi::um::wingdi::{
+    CreateSolidBrush, DeleteObject, SetBkMode, SetTextColor, RGB, TRANSPARENT,
+};
+use winapi::um::winuser::{
+    BeginPaint, CreateWindowExW, DefWindowProcW, DispatchMessageW, DrawTextW, EndPaint, FillRect,
+    FrameRect, GetClientRect, GetMessageW, GetWindowLongPtrW, InvalidateRect, PostMessageW,
+    PostQuitMessage, RegisterClassExW, SetLayeredWindowAttributes, SetWindowLongPtrW, SetWindowPos,
+    ShowWindow, TranslateMessage, DT_CENTER, DT_END_ELLIPSIS, DT_NOPREFIX, DT_SINGLELINE,
+    DT_VCENTER, GWLP_USERDATA, HWND_TOPMOST, LWA_ALPHA, PAINTSTRUCT, SWP_NOACTIVATE,
+    SWP_SHOWWINDOW, SW_HIDE, WM_CLOSE, WM_DESTROY, WM_LBUTTONDOWN, WM_PAINT, WNDCLASSEXW,
+    WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_POPUP,
+};
+
+use grout::Rect;
+
+use crate::str_to_wide;
+use crate::window::Window;
+use crate::Message;
+use crate::{CHANNEL, STACKS};
+
+/// Opens the tab strip of the stack with `id`. Runs until the strip is closed with
+/// `close_tab_strip`.
+pub fn spawn_tab_strip(id: usize) {
+    thread::spawn(move || unsafe {
+        let hInstance = GetModuleHandleW(ptr::null());
+
+        let class_name = str_to_wide!("Grout Tab Strip");
+
+        let mut class = mem::zeroed::<WNDCLASSEXW>();
+        class.cbSize = mem::size_of::<WNDCLASSEXW>() as u32;
+        class.lpfnWndProc = Some(callback);
+        class.hInstance = hInstance;
+        class.lpszClassName = class_name.as_ptr();
+        class.hbrBackground = CreateSolidBrush(RGB(44, 44, 44));
+
+        RegisterClassExW(&class);
+
+        let hwnd = CreateWindowExW(
+            WS_EX_LAYERED | WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW | WS_EX_TOPMOST,
+            class_name.as_ptr(),
+            ptr::null(),
+            WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            hInstance,
+            ptr::null_mut(),
+        );
+
+        SetLayeredWindowAttributes(hwnd, 0, 230, LWA_ALPHA);
+        SetWindowLongPtrW(hwnd, GWLP_USERDATA, id as isize);
+
+        let _ = &CHANNEL.0.clone().send(Message::TabStrip(id, Window(hwnd)));
+
+        let mut msg = mem::zeroed();
+        while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+/// Shows the strip over `area`. The strip can't be owned by the stacked windows, as
+/// they belong to other processes, so it's kept topmost instead and moved along with
+/// its zone.
+pub fn place_tab_strip(strip: Window, area: Rect) {
+    unsafe {
+        SetWindowPos(
+            strip.0,
+            HWND_TOPMOST,
+            area.x,
+            area.y,
+            area.width,
+            area.height,
+            SWP_NOACTIVATE | SWP_SHOWWINDOW,
+        );
+
+        InvalidateRect(strip.0, ptr::null(), 0);
+    }
+}
+
+/// Hides the strip while its stack isn't shown, such as when minimized
+pub fn hide_tab_strip(strip: Window) {
+    unsafe {
+        ShowWindow(strip.0, SW_HIDE);
+    }
+}
+
+pub fn close_tab_strip(strip: Window) {
+    unsafe {
+        PostMessageW(strip.0, WM_CLOSE, 0, 0);
+    }
+}
+
+unsafe extern "system" fn callback(
+    hWnd: HWND,
+    Msg: UINT,
+    wParam: WPARAM,
+    lParam: LPARAM,
+) -> LRESULT {
+    let id = GetWindowLongPtrW(hWnd, GWLP_USERDATA) as usize;
+
+    match Msg {
+        WM_PAINT => draw(Window(hWnd), id),
+        WM_LBUTTONDOWN => {
+            let x = LOWORD(lParam as u32) as i32;
+
+            let mut area = mem::zeroed();
+            GetClientRect(hWnd, &mut area);
+            let area: Rect = area.into();
+
+            let clicked = STACKS.lock().unwrap().get(id).and_then(|stack| {
+                let idx = x * stack.windows.len() as i32 / area.width.max(1);
+                stack.windows.get(idx as usize).copied()
+            });
+
+            if let Some(window) = clicked {
+                window.activate();
+            }
+        }
+        WM_DESTROY => PostQuitMessage(0),
+        _ => {}
+    }
+
+    DefWindowProcW(hWnd, Msg, wParam, lParam)
+}
+
+/// One tab per window in the stack, with the visible window's tab highlighted
+unsafe fn draw(strip: Window, id: usize) {
+    let mut paint: PAINTSTRUCT = mem::zeroed();
+
+    let hdc = BeginPaint(strip.0, &mut paint);
+
+    let mut area = mem::zeroed();
+    GetClientRect(strip.0, &mut area);
+    let area: Rect = area.into();
+
+    // Titles are read after releasing the lock, as reading one sends the window a
+    // message that could wait on the thread holding it
+    let stack = STACKS
+        .lock()
+        .unwrap()
+        .get(id)
+        .map(|stack| (stack.windows.clone(), stack.visible_window()));
+
+    if let Some((windows, visible_window)) = stack {
+        let count = windows.len() as i32;
+
+        SetBkMode(hdc, TRANSPARENT as i32);
+
+        for (idx, window) in windows.into_iter().enumerate() {
+            let x = area.width * idx as i32 / count;
+            let tab_area = Rect {
+                x,
+                y: 0,
+                width: area.width * (idx as i32 + 1) / count - x,
+                height: area.height,
+            };
+
+            let (fill_color, text_color) = if window == visible_window {
+                (RGB(0, 77, 128), RGB(255, 255, 255))
+            } else {
+                (RGB(70, 70, 70), RGB(200, 200, 200))
+            };
+
+            let fill_brush = CreateSolidBrush(fill_color);
+            let frame_brush = CreateSolidBrush(RGB(0, 0, 0));
+
+            FillRect(hdc, &tab_area.into(), fill_brush);
+            FrameRect(hdc, &tab_area.into(), frame_brush);
+
+            DeleteObject(fill_brush as *mut _);
+            DeleteObject(frame_brush as *mut _);
+
+            SetTextColor(hdc, text_color);
+
+            let title = str_to_wide!(window.title());
+            let mut text_area = Rect {
+                x: tab_area.x + 4,
+                width: tab_area.width - 8,
+                ..tab_area
+            }
+            .into();
+
+            DrawTextW(
+                hdc,
+                title.as_ptr(),
+                -1,
+                &mut text_area,
+                DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS | DT_NOPREFIX,
+            );
+        }
+    }
+
+    EndPaint(strip.0, &paint);
+}